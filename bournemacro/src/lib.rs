@@ -50,7 +50,7 @@ impl Parse for Null {
 
 impl Parse for Value {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if let Ok(_) = input.parse::<Null>() {
+        if input.parse::<Null>().is_ok() {
             Ok(Value::Null)
         } else if let Ok(Array(array)) = input.parse::<Array>() {
             Ok(Value::Array(array))
@@ -79,15 +79,15 @@ impl Parse for KeyValuePair {
 }
 
 impl Value {
-    fn to_tokenstream(self) -> proc_macro2::TokenStream {
+    fn into_tokenstream(self) -> proc_macro2::TokenStream {
         use quote::quote;
         match self {
             Value::Null => quote!(bourne::Value::Null),
             Value::Object(object) => {
                 let capacity = object.len();
                 let inserts = object.into_iter().map(|KeyValuePair { key, value }| {
-                    let value = value.to_tokenstream();
-                    quote! { map.insert((#key).to_owned(), #value); }
+                    let value = value.into_tokenstream();
+                    quote! { map.insert((#key).into(), #value); }
                 }).collect::<Vec<_>>();
                 quote! {
                     {
@@ -100,7 +100,7 @@ impl Value {
             Value::Array(array) => {
                 let capacity = array.len();
                 let lines = array.into_iter().map(|value| {
-                    let value = value.to_tokenstream();
+                    let value = value.into_tokenstream();
                     quote!{ array.push(#value); }
                 }).collect::<Vec<_>>();
                 quote! {
@@ -119,21 +119,14 @@ impl Value {
 }
 
 /// Create a JSON object. Expressions are allowed as values as long as the result is convertible to a Value.
-/// Example:
-/// ```rust,no_run
-/// let number = 3.14;
-/// let value = json!(
-///     {
-///         "number" : number
-///     }
-/// );
-/// ```
+///
+/// Use it through the `bourne::json!` re-export, whose documentation has a tested example.
 #[proc_macro]
 pub fn json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     if input.is_empty() {
         quote::quote!{ bourne::Value::Null }.into()
     } else {
         let value = parse_macro_input!(input as Value);
-        value.to_tokenstream().into()
+        value.into_tokenstream().into()
     }
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+//! Builds `bourne` without its `std` feature. Run `cargo test -p bourne_no_std_check`
+//! on its own, as building the whole workspace unifies `bourne`'s features.
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+
+use bourne::{parse::ParseResult, Value};
+
+/// Parse `source` and serialize it back as compact JSON.
+pub fn round_trip(source: &str) -> ParseResult<String> {
+    Ok(source.parse::<Value>()?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() -> ParseResult<()> {
+        assert_eq!(round_trip(r#" [1, 2.5, "x\n", {"b": null}] "#)?, r#"[1,2.5,"x\n",{"b":null}]"#);
+        assert!(round_trip("[1,").is_err());
+        Ok(())
+    }
+}
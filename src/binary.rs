@@ -0,0 +1,36 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::Value;
+use crate::prelude::*;
+
+impl Value {
+    /// Encode `bytes` as a [Value::String] of standard, padded base64 (RFC 4648).
+    pub fn from_base64_bytes(bytes: &[u8]) -> Value {
+        Value::String(STANDARD.encode(bytes))
+    }
+
+    /// Decode a [Value::String] of standard, padded base64 (RFC 4648). Returns [None] if
+    /// self is not a [Value::String] or is not valid base64.
+    pub fn as_base64_bytes(&self) -> Option<Vec<u8>> {
+        STANDARD.decode(self.as_str()?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn base64_test() {
+        let bytes = [0u8, 1, 2, 254, 255, b'x'];
+        let value = Value::from_base64_bytes(&bytes);
+        assert_eq!(value, "AAEC/v94");
+        assert_eq!(value.as_base64_bytes().as_deref(), Some(&bytes[..]));
+        assert_eq!(Value::from_base64_bytes(&[]).as_base64_bytes(), Some(Vec::new()));
+        assert_eq!(Value::from("aGk=").as_base64_bytes(), Some(b"hi".to_vec()));
+        assert_eq!(Value::from("not base64!").as_base64_bytes(), None);
+        assert_eq!(Value::from(42i64).as_base64_bytes(), None);
+        assert_eq!(Value::Null.as_base64_bytes(), None);
+    }
+}
@@ -0,0 +1,707 @@
+use alloc::borrow::Cow;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+use crate::{Number, Resolved, Value};
+use crate::prelude::*;
+
+/// 2^127 as an [f64], the first float past the range of [i128].
+const I128_LIMIT: f64 = 170141183460469231731687303715884105728.0;
+
+/// Returns the [i128] that `float` is exactly equal to, if there is one.
+pub(crate) fn integral_value_i128(float: f64) -> Option<i128> {
+    // In range, the cast truncates towards zero. `f64::fract` would need `std`.
+    if (-I128_LIMIT..I128_LIMIT).contains(&float) && float as i128 as f64 == float {
+        Some(float as i128)
+    } else {
+        None
+    }
+}
+
+/// Returns the [i64] that `float` is exactly equal to, if there is one.
+pub(crate) fn integral_value(float: f64) -> Option<i64> {
+    integral_value_i128(float).and_then(|int| i64::try_from(int).ok())
+}
+
+/// Compare an integer to an [f64] without losing precision.
+fn cmp_int_float(int: i128, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+    if float >= I128_LIMIT {
+        return Some(Ordering::Less);
+    }
+    if float < -I128_LIMIT {
+        return Some(Ordering::Greater);
+    }
+    // In range, the cast truncates towards zero. `f64::trunc` would need `std`.
+    let trunc = float as i128;
+    Some(int.cmp(&trunc).then_with(|| {
+        // The integer parts are equal, so the fraction decides.
+        let trunc = trunc as f64;
+        if float > trunc {
+            Ordering::Less
+        } else if float < trunc {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }))
+}
+
+/// The text of a [Number::Raw] as an exact decimal `±0.d₁d₂…dₙ × 10^exponent`, so that
+/// raw numbers compare without rounding through an [f64].
+struct Decimal<'a> {
+    negative: bool,
+    /// The digits before and after the decimal point, as written.
+    integer: &'a [u8],
+    fraction: &'a [u8],
+    /// The range of significant digits in `integer` followed by `fraction`, without
+    /// leading or trailing zeros. Empty for zero.
+    significant: core::ops::Range<usize>,
+    exponent: i128,
+}
+
+impl<'a> Decimal<'a> {
+    /// Parse the text of a [Number::Raw]. Returns [None] if it isn't a number.
+    fn parse(text: &'a str) -> Option<Self> {
+        let (negative, text) = match text.as_bytes() {
+            [b'-', rest @ ..] => (true, rest),
+            [b'+', rest @ ..] => (false, rest),
+            text => (false, text),
+        };
+        let (mantissa, exponent) = match text.iter().position(|byte| matches!(byte, b'e' | b'E')) {
+            Some(index) => (&text[..index], core::str::from_utf8(&text[index + 1..]).ok()?.parse::<i64>().ok()?),
+            None => (text, 0),
+        };
+        let (integer, fraction) = match mantissa.iter().position(|&byte| byte == b'.') {
+            Some(index) => (&mantissa[..index], &mantissa[index + 1..]),
+            None => (mantissa, &[][..]),
+        };
+        if integer.is_empty() && fraction.is_empty() || !integer.iter().chain(fraction).all(u8::is_ascii_digit) {
+            return None;
+        }
+        let digits = || integer.iter().chain(fraction);
+        let start = digits().position(|&digit| digit != b'0').unwrap_or(0);
+        let len = integer.len() + fraction.len();
+        let end = len - digits().rev().position(|&digit| digit != b'0').unwrap_or(len);
+        Some(Decimal {
+            negative,
+            integer,
+            fraction,
+            significant: start..end,
+            exponent: exponent as i128 + integer.len() as i128 - start as i128,
+        })
+    }
+
+    fn digits(&self) -> impl Iterator<Item = &u8> {
+        self.integer.iter().chain(self.fraction)
+            .take(self.significant.end)
+            .skip(self.significant.start)
+    }
+
+    /// -1, 0 or 1, where `-0` counts as zero.
+    fn signum(&self) -> i8 {
+        match (self.significant.is_empty(), self.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        }
+    }
+
+    /// The integer this is exactly equal to, if it is one that fits in an [i128].
+    fn integer(&self) -> Option<i128> {
+        if self.signum() == 0 {
+            return Some(0);
+        }
+        let digits = self.significant.len() as i128;
+        // i128::MAX has 39 digits, so longer integers can't fit.
+        if self.exponent < digits || self.exponent > 39 {
+            return None;
+        }
+        let zeros = core::iter::repeat_n(&b'0', (self.exponent - digits) as usize);
+        self.digits().chain(zeros).try_fold(0i128, |int, &digit| {
+            let digit = (digit - b'0') as i128;
+            let int = int.checked_mul(10)?;
+            if self.negative { int.checked_sub(digit) } else { int.checked_add(digit) }
+        })
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        let signum = self.signum();
+        signum.cmp(&other.signum()).then_with(|| {
+            if signum == 0 {
+                return Ordering::Equal;
+            }
+            let magnitude = self.exponent.cmp(&other.exponent).then_with(|| self.digits().cmp(other.digits()));
+            if signum < 0 { magnitude.reverse() } else { magnitude }
+        })
+    }
+}
+
+impl Number {
+    /// The [Number::Int], or [Number::Uint] if it only fits in a [u64], that the text of a
+    /// [Number::Raw] is exactly equal to. Like [Number::integral], this returns [None] for
+    /// text with a fractional part, outside the [u64] and [i64] ranges, and `-0`.
+    pub(crate) fn integral_text(text: &str) -> Option<Number> {
+        let decimal = Decimal::parse(text)?;
+        if decimal.signum() == 0 && decimal.negative {
+            return None;
+        }
+        let int = decimal.integer()?;
+        match i64::try_from(int) {
+            Ok(int) => Some(Number::Int(int)),
+            Err(_) => u64::try_from(int).ok().map(Number::Uint),
+        }
+    }
+}
+
+/// The exact decimal expansion of a finite [f64], as `digits` or `digitse-exponent`.
+///
+/// Every finite float is `mantissa × 2^exponent`. A positive exponent makes that an
+/// integer, and a negative one equals `mantissa × 5^-exponent × 10^exponent`, so either
+/// way it takes multiplying the mantissa by a power of 2 or 5. The product is built in
+/// base 10⁹ limbs, least significant first, and has at most 767 digits.
+fn exact_float_text(float: f64) -> String {
+    let bits = float.to_bits();
+    let biased_exponent = (bits >> 52 & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mantissa, exponent) = match biased_exponent {
+        0 => (fraction, -1074),
+        _ => (fraction | 1 << 52, biased_exponent - 1075),
+    };
+    const BASE: u64 = 1_000_000_000;
+    let mut limbs = vec![(mantissa % BASE) as u32, (mantissa / BASE % BASE) as u32, (mantissa / BASE / BASE) as u32];
+    let mut multiply = |factor: u32| {
+        let carry = limbs.iter_mut().fold(0, |carry, limb| {
+            let product = *limb as u64 * factor as u64 + carry;
+            *limb = (product % BASE) as u32;
+            product / BASE
+        });
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+    };
+    // 2^29 and 5^13 are the largest powers below 10⁹ and 2^32, which keep the products
+    // in a u64.
+    let (factor, chunk, count) = match exponent {
+        0.. => (2u32, 29, exponent as u32),
+        _ => (5u32, 13, exponent.unsigned_abs()),
+    };
+    (0..count / chunk).for_each(|_| multiply(factor.pow(chunk)));
+    multiply(factor.pow(count % chunk));
+    while limbs.len() > 1 && limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    let mut text = String::with_capacity(limbs.len() * 9 + 7);
+    if float.is_sign_negative() {
+        text.push('-');
+    }
+    let mut limbs = limbs.iter().rev();
+    text.push_str(&limbs.next().unwrap_or(&0).to_string());
+    limbs.for_each(|limb| text.push_str(&format!("{limb:09}")));
+    if exponent < 0 {
+        text.push_str(&format!("e{exponent}"));
+    }
+    text
+}
+
+/// The text of a number for comparing it exactly as a [Decimal], or [None] for `NaN`
+/// and infinities.
+fn exact_text(number: &Number) -> Option<Cow<'_, str>> {
+    match *number {
+        Number::Float(float) if float.is_finite() => Some(Cow::Owned(exact_float_text(float))),
+        Number::Float(_) => None,
+        Number::Int(int) => Some(Cow::Owned(int.to_string())),
+        Number::Uint(uint) => Some(Cow::Owned(uint.to_string())),
+        Number::Raw(ref text) => Some(Cow::Borrowed(text)),
+    }
+}
+
+/// Compare two numbers exactly as decimals if either is a [Number::Raw], so that the
+/// comparison is transitive across variants. Returns [None] for any other pair, which
+/// compare through [Number::resolve], and for pairs with `NaN` or raw text that isn't
+/// a number.
+fn cmp_raw(lhs: &Number, rhs: &Number) -> Option<Ordering> {
+    if !matches!(lhs, Number::Raw(_)) && !matches!(rhs, Number::Raw(_)) {
+        return None;
+    }
+    match (exact_text(lhs), exact_text(rhs)) {
+        (Some(lhs), Some(rhs)) => Some(Decimal::parse(&lhs)?.cmp(&Decimal::parse(&rhs)?)),
+        // Raw numbers are finite, so they are between the infinities.
+        (Some(raw), None) if Decimal::parse(&raw).is_some() => infinity_signum(rhs).map(|sign| 0.cmp(&sign)),
+        (None, Some(raw)) if Decimal::parse(&raw).is_some() => infinity_signum(lhs).map(|sign| sign.cmp(&0)),
+        _ => None,
+    }
+}
+
+/// -1 or 1 for the infinities, [None] for any other number.
+fn infinity_signum(number: &Number) -> Option<i8> {
+    match *number {
+        Number::Float(float) if float == f64::INFINITY => Some(1),
+        Number::Float(float) if float == f64::NEG_INFINITY => Some(-1),
+        _ => None,
+    }
+}
+
+impl PartialEq for Number {
+    /// Numbers are compared by value, so `Int(3) == Uint(3) == Float(3.0)`. A
+    /// [Number::Raw] compares exactly by the decimal number it spells, and a
+    /// [Number::Float] by the exact binary value it holds. So `Raw("0.1")` doesn't equal
+    /// `Float(0.1)`, which is 0.1000000000000000055511151231257827021181583404541015625.
+    ///
+    /// `NaN` is considered equal to itself so that [Number] (and [Value]) can implement [Eq].
+    fn eq(&self, other: &Self) -> bool {
+        if let Some(ordering) = cmp_raw(self, other) {
+            return ordering == Ordering::Equal;
+        }
+        match (self.resolve(), other.resolve()) {
+            (Resolved::Float(lhs), Resolved::Float(rhs)) => lhs == rhs || (lhs.is_nan() && rhs.is_nan()),
+            (Resolved::Float(float), int) | (int, Resolved::Float(float)) => integral_value_i128(float) == int.integer(),
+            (lhs, rhs) => lhs.integer() == rhs.integer(),
+        }
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    /// Numbers are ordered by value. `NaN` is only comparable to itself.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if let Some(ordering) = cmp_raw(self, other) {
+            return Some(ordering);
+        }
+        match (self.resolve(), other.resolve()) {
+            (Resolved::Float(lhs), Resolved::Float(rhs)) if lhs.is_nan() && rhs.is_nan() => Some(Ordering::Equal),
+            (Resolved::Float(lhs), Resolved::Float(rhs)) => lhs.partial_cmp(&rhs),
+            (int, Resolved::Float(float)) => cmp_int_float(int.integer()?, float),
+            (Resolved::Float(float), int) => cmp_int_float(int.integer()?, float).map(Ordering::reverse),
+            (lhs, rhs) => Some(lhs.integer()?.cmp(&rhs.integer()?)),
+        }
+    }
+}
+
+/// A total order over numbers for [Value]'s [Ord]: by value, with `NaN` after every
+/// other number.
+fn total_cmp_numbers(lhs: &Number, rhs: &Number) -> Ordering {
+    lhs.partial_cmp(rhs).unwrap_or_else(|| {
+        // Only NaN is incomparable, and NaN is equal to itself.
+        let is_nan = |number: &Number| matches!(number.resolve(), Resolved::Float(float) if float.is_nan());
+        is_nan(lhs).cmp(&is_nan(rhs))
+    })
+}
+
+impl Hash for Number {
+    /// Hashing is consistent with [PartialEq]: all integer variants and integral floats
+    /// hash by their integer value, and all `NaN`s hash the same. A [Number::Raw] that is
+    /// exactly an integer hashes as that integer, and otherwise as the [f64] it parses
+    /// to. Any float it is equal to is that [f64], and raw numbers that are equal always
+    /// parse to the same one.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if let Number::Raw(text) = self {
+            if let Some(int) = Decimal::parse(text).and_then(|decimal| decimal.integer()) {
+                return Some(int).hash(state);
+            }
+        }
+        let number = self.resolve();
+        let Resolved::Float(float) = number else {
+            return number.integer().hash(state);
+        };
+        match integral_value_i128(float) {
+            Some(int) => Some(int).hash(state),
+            None if float.is_nan() => f64::NAN.to_bits().hash(state),
+            None => float.to_bits().hash(state),
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Ord for Value {
+    /// Values of different types are ordered `null < boolean < number < string < array < object`.
+    ///
+    /// Within a type, `false < true`, numbers compare by value (see [Number]) with `NaN`
+    /// last, strings compare lexically by byte, and arrays lexicographically by element.
+    /// Objects compare as their entries sorted by key, lexicographically by key then value.
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn rank(value: &Value) -> u8 {
+            match value {
+                Value::Null => 0,
+                Value::Boolean(_) => 1,
+                Value::Number(_) => 2,
+                Value::String(_) => 3,
+                Value::Array(_) => 4,
+                Value::Object(_) => 5,
+            }
+        }
+        match (self, other) {
+            (Value::Boolean(lhs), Value::Boolean(rhs)) => lhs.cmp(rhs),
+            (Value::Number(lhs), Value::Number(rhs)) => total_cmp_numbers(lhs, rhs),
+            (Value::String(lhs), Value::String(rhs)) => lhs.cmp(rhs),
+            (Value::Array(lhs), Value::Array(rhs)) => lhs.cmp(rhs),
+            (Value::Object(lhs), Value::Object(rhs)) => {
+                let mut lhs = lhs.iter().collect::<Vec<_>>();
+                let mut rhs = rhs.iter().collect::<Vec<_>>();
+                lhs.sort_unstable_by_key(|&(key, _)| key);
+                rhs.sort_unstable_by_key(|&(key, _)| key);
+                lhs.cmp(&rhs)
+            }
+            (lhs, rhs) => rank(lhs).cmp(&rank(rhs)),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    /// See [Value]'s [Ord].
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq<str> for Value {
+    /// True if self is a [Value::String] equal to `other`.
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, Value::String(string) if string == other)
+    }
+}
+
+impl PartialEq<&str> for Value {
+    /// True if self is a [Value::String] equal to `other`.
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<String> for Value {
+    /// True if self is a [Value::String] equal to `other`.
+    fn eq(&self, other: &String) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<bool> for Value {
+    /// True if self is a [Value::Boolean] equal to `other`.
+    fn eq(&self, other: &bool) -> bool {
+        matches!(self, Value::Boolean(boolean) if boolean == other)
+    }
+}
+
+impl PartialEq<i64> for Value {
+    /// True if self is a [Value::Number] with the same value as `other`. See [Number]'s [PartialEq].
+    fn eq(&self, other: &i64) -> bool {
+        matches!(self, Value::Number(number) if *number == Number::Int(*other))
+    }
+}
+
+impl PartialEq<u64> for Value {
+    /// True if self is a [Value::Number] with the same value as `other`. See [Number]'s [PartialEq].
+    fn eq(&self, other: &u64) -> bool {
+        matches!(self, Value::Number(number) if *number == Number::Uint(*other))
+    }
+}
+
+impl PartialEq<f64> for Value {
+    /// True if self is a [Value::Number] with the same value as `other`. See [Number]'s [PartialEq].
+    fn eq(&self, other: &f64) -> bool {
+        matches!(self, Value::Number(number) if *number == Number::Float(*other))
+    }
+}
+
+impl Value {
+    /// Like `==`, but numbers must also be of the same kind: an integer ([Number::Int]
+    /// or [Number::Uint]) never equals a [Number::Float], so `3` and `3.0` differ.
+    /// [Number::Raw] text is compared as the number it represents.
+    pub fn strict_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                let is_float = |number: &Number| matches!(number.resolve(), Resolved::Float(_));
+                is_float(lhs) == is_float(rhs) && lhs == rhs
+            }
+            (Value::Array(lhs), Value::Array(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| lhs.strict_eq(rhs))
+            }
+            (Value::Object(lhs), Value::Object(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().all(|(key, lhs)| {
+                    rhs.get(key).is_some_and(|rhs| lhs.strict_eq(rhs))
+                })
+            }
+            (lhs, rhs) => lhs == rhs,
+        }
+    }
+
+    /// Check that self is contained in `other`: every key of a [Value::Object] in self
+    /// must be present in `other` with a value that self's value is in turn a subset of,
+    /// while `other` may have extra keys. Arrays must have the same length, and each
+    /// element must be a subset of the element at the same index. Other values are
+    /// compared with `==`.
+    ///
+    /// Useful for asserting that a document has at least some expected structure.
+    pub fn is_subset_of(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Array(lhs), Value::Array(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| lhs.is_subset_of(rhs))
+            }
+            (Value::Object(lhs), Value::Object(rhs)) => {
+                lhs.iter().all(|(key, lhs)| rhs.get(key).is_some_and(|rhs| lhs.is_subset_of(rhs)))
+            }
+            (lhs, rhs) => lhs == rhs,
+        }
+    }
+}
+
+/// FNV-1a, used to hash object entries on their own. Any hasher with fixed keys would
+/// do; `std`'s `DefaultHasher` isn't available without `std`.
+struct EntryHasher(u64);
+
+impl EntryHasher {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for EntryHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl Hash for Value {
+    /// Objects are hashed independently of their key order.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            Value::Null => (),
+            Value::Boolean(boolean) => boolean.hash(state),
+            Value::Number(number) => number.hash(state),
+            Value::String(string) => string.hash(state),
+            Value::Array(array) => array.hash(state),
+            Value::Object(object) => {
+                // Combine the entry hashes with a commutative operation so that
+                // iteration order doesn't matter.
+                let combined = object.iter().fold(0u64, |acc, entry| {
+                    let mut hasher = EntryHasher::new();
+                    entry.hash(&mut hasher);
+                    acc.wrapping_add(hasher.finish())
+                });
+                object.len().hash(state);
+                combined.hash(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    use crate::{Number, Value};
+
+    #[test]
+    fn number_eq_test() {
+        assert_eq!(Number::Int(3), Number::Float(3.0));
+        assert_eq!(Number::Float(f64::NAN), Number::Float(f64::NAN));
+        assert_ne!(Number::Int(i64::MAX), Number::Float(i64::MAX as f64));
+        assert!(Number::Int(2) < Number::Float(2.5));
+        assert!(Number::Float(-2.5) < Number::Int(-2));
+        assert!(Number::Int(i64::MAX) < Number::Float(1e19));
+        assert_eq!(Number::Uint(3), Number::Int(3));
+        assert_eq!(Number::Uint(1 << 63), Number::Float(9223372036854775808.0));
+        assert!(Number::Int(-1) < Number::Uint(0));
+        assert!(Number::Uint(u64::MAX) > Number::Int(i64::MAX));
+        assert!(Number::Uint(u64::MAX) < Number::Float(1e20));
+    }
+
+    #[test]
+    fn raw_number_eq_test() {
+        let raw = |text: &str| Number::Raw(text.to_string());
+        assert_ne!(raw("123456789012345678901234567890"), raw("123456789012345678901234567891"));
+        assert!(raw("123456789012345678901234567890") < raw("123456789012345678901234567891"));
+        assert_ne!(raw("0.1"), raw("0.10000000000000000001"));
+        assert!(raw("0.1") < raw("0.10000000000000000001"));
+        assert!(!Value::Number(raw("0.1")).strict_eq(&Value::Number(raw("0.10000000000000000001"))));
+        assert!(raw("-0.10000000000000000001") < raw("-0.1"));
+        assert_eq!(raw("1e2"), raw("100.00"));
+        assert_eq!(raw("0.05E+3"), raw("50"));
+        assert_eq!(raw("-0"), raw("0.0e7"));
+        assert!(raw("-1e-99999") < raw("0") && raw("0") < raw("1e-99999"));
+        assert!(raw("9.99") < raw("10") && raw("-10") < raw("-9.99"));
+        // Against other variants too, raw numbers compare exactly.
+        assert_ne!(raw("0.1"), Number::Float(0.1));
+        assert_eq!(raw("0.1000000000000000055511151231257827021181583404541015625"), Number::Float(0.1));
+        assert!(raw("5e-324") > Number::Float(5e-324));
+        assert!(raw("4.9406564584124654e-324") < Number::Float(5e-324));
+        assert!(raw("4.9406564584124655e-324") > Number::Float(5e-324));
+        assert!(raw("-1.7976931348623157e308") > Number::Float(f64::MIN));
+        assert_eq!(raw("1180591620717411303424"), Number::Float(1180591620717411303424.0));
+        assert_ne!(raw("9007199254740993"), Number::Float(9007199254740992.0));
+        assert!(raw("1e400") < Number::Float(f64::INFINITY) && raw("-1e400") > Number::Float(f64::NEG_INFINITY));
+        assert_eq!(raw("18446744073709551615"), Number::Uint(u64::MAX));
+        assert_eq!(raw("-0.0"), Number::Int(0));
+        let set = ["1e2", "100", "100.0", "0.1", "0.10000000000000000001"].map(raw).into_iter().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn raw_number_transitivity_test() {
+        let raw = |text: &str| Number::Raw(text.to_string());
+        let numbers = [
+            raw("0.1"), raw("0.10000000000000000001"), raw("0.1000000000000000055511151231257827021181583404541015625"),
+            Number::Float(0.1), Number::Float(0.1 + f64::EPSILON / 16.0),
+            raw("9007199254740993"), raw("9007199254740992.0"), raw("9.007199254740992e15"),
+            Number::Float(9007199254740992.0), Number::Int(9007199254740993), Number::Uint(9007199254740992),
+            raw("-0"), Number::Float(-0.0), Number::Int(0), raw("1e-400"),
+            raw("1e400"), Number::Float(f64::INFINITY), Number::Float(f64::MAX),
+        ];
+        let mut set = HashSet::new();
+        for a in &numbers {
+            for b in &numbers {
+                assert_eq!(a.partial_cmp(b), b.partial_cmp(a).map(Ordering::reverse), "{a:?} {b:?}");
+                assert_eq!(a == b, a.partial_cmp(b) == Some(Ordering::Equal), "{a:?} {b:?}");
+                for c in &numbers {
+                    if a <= b && b <= c {
+                        assert!(a <= c, "{a:?} <= {b:?} <= {c:?}");
+                        assert_eq!(a == c, a == b && b == c, "{a:?} {b:?} {c:?}");
+                    }
+                }
+            }
+            // Numbers that are equal hash the same, so each value is kept once.
+            if !set.iter().any(|other| other == a) {
+                set.insert(a.clone());
+            }
+            assert!(set.contains(a), "{a:?}");
+        }
+    }
+
+    #[test]
+    fn strict_eq_test() -> Result<(), crate::error::ParseError> {
+        let int = Value::from(3i64);
+        let float = Value::from(3.0);
+        assert_eq!(int, float);
+        assert!(!int.strict_eq(&float));
+        assert!(int.strict_eq(&Value::Number(Number::Uint(3))));
+        assert!(float.strict_eq(&Value::from(3.0)));
+        assert!(Value::from(f64::NAN).strict_eq(&Value::from(f64::NAN)));
+
+        let lhs = Value::from_str(r#"{"a": [1, {"b": 2.5}], "c": "x"}"#)?;
+        let rhs = Value::from_str(r#"{"c": "x", "a": [1, {"b": 2.5}]}"#)?;
+        assert!(lhs.strict_eq(&rhs));
+        let rhs = Value::from_str(r#"{"c": "x", "a": [1.0, {"b": 2.5}]}"#)?;
+        assert_eq!(lhs, rhs);
+        assert!(!lhs.strict_eq(&rhs));
+        assert!(!lhs.strict_eq(&Value::from_str(r#"{"a": [1, {"b": 2.5}]}"#)?));
+        Ok(())
+    }
+
+    #[test]
+    fn is_subset_of_test() -> Result<(), crate::error::ParseError> {
+        let response = Value::from_str(r#"
+            {
+                "id": 7,
+                "user": { "name": "Fred", "email": "fred@example.com" },
+                "roles": [{ "name": "admin", "since": 2020 }, { "name": "dev" }]
+            }
+        "#)?;
+        let expected = Value::from_str(r#"{"user": {"name": "Fred"}, "roles": [{"name": "admin"}, {}]}"#)?;
+        assert!(expected.is_subset_of(&response));
+        assert!(!response.is_subset_of(&expected));
+        assert!(Value::object().is_subset_of(&response));
+        assert!(response.is_subset_of(&response));
+        assert!(Value::from_str(r#"{"id": 7.0}"#)?.is_subset_of(&response));
+
+        assert!(!Value::from_str(r#"{"user": {"name": "Barney"}}"#)?.is_subset_of(&response));
+        assert!(!Value::from_str(r#"{"missing": null}"#)?.is_subset_of(&response));
+        assert!(!Value::from_str(r#"{"roles": [{"name": "admin"}]}"#)?.is_subset_of(&response));
+        assert!(!Value::from_str(r#"{"id": "7"}"#)?.is_subset_of(&response));
+        Ok(())
+    }
+
+    #[test]
+    fn ord_test() -> Result<(), crate::error::ParseError> {
+        let mut values = [
+            r#"{"b": 1}"#, r#"{"a": 2, "b": 1}"#, r#"{"a": 1, "z": 0}"#, "{}",
+            "[1, 2]", "[1]", "[]", "[0, 5]",
+            r#""b""#, r#""B""#, r#""ab""#, "\"\"",
+            "2.5", "-1", "3", "1e300", "2",
+            "true", "false", "null",
+        ].into_iter().map(Value::from_str).collect::<Result<Vec<_>, _>>()?;
+        values.push(Value::from(f64::NAN));
+        values.push(Value::Number(Number::Uint(u64::MAX)));
+        values.sort();
+        let expected = [
+            "null", "false", "true",
+            "-1", "2", "2.5", "3", "18446744073709551615", "1e300", "NaN",
+            "\"\"", r#""B""#, r#""ab""#, r#""b""#,
+            "[]", "[0,5]", "[1]", "[1,2]",
+            "{}", r#"{"a":1,"z":0}"#, r#"{"a":2,"b":1}"#, r#"{"b":1}"#,
+        ];
+        let sorted = values.iter().map(|value| match value {
+            Value::Number(Number::Float(float)) if float.is_nan() => "NaN".to_owned(),
+            Value::Number(Number::Float(float)) if *float == 1e300 => "1e300".to_owned(),
+            value => crate::format::Formatter::new().sort_keys(true).format(value),
+        }).collect::<Vec<_>>();
+        assert_eq!(sorted, expected);
+
+        assert_eq!(Value::from(3i64).cmp(&Value::from(3.0)), Ordering::Equal);
+        assert_eq!(Value::from_str(r#"{"a": 1, "b": 2}"#)?.cmp(&Value::from_str(r#"{"b": 2, "a": 1}"#)?), Ordering::Equal);
+        assert!(Value::Null < Value::Boolean(false));
+        Ok(())
+    }
+
+    #[test]
+    fn literal_eq_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "tag": null,
+                "registered": true,
+                "age": 197,
+                "name": "Fred",
+                "classes": ["Algebra", "History of Programming"],
+                "rgb_for_some_reason": { "r": 4, "g": 7.5, "b": 3 }
+            }
+        "#)?;
+        assert!(value["name"] == "Fred");
+        assert!(value["name"] == *"Fred");
+        let name = String::from("Fred");
+        assert!(value["name"] == name);
+        assert!(value["name"] != "Barney");
+        assert!(value["registered"] == true);
+        assert!(value["age"] == 197i64);
+        assert!(value["age"] == 197u64);
+        assert!(value["age"] == 197.0);
+        assert!(value["rgb_for_some_reason"]["g"] == 7.5);
+        assert!(value["classes"][1] == "History of Programming");
+        assert!(value["age"] != "197");
+        assert!(value["name"] != true);
+        assert!(value["tag"] != 0i64);
+        assert!(value["missing"] != false);
+        Ok(())
+    }
+
+    #[test]
+    fn hash_set_test() -> Result<(), crate::error::ParseError> {
+        let mut set = [
+            r#"{"a": 1, "b": [true, null]}"#,
+            r#"{"b": [true, null], "a": 1.0}"#,
+            r#"{"a": 1, "b": [null, true]}"#,
+            r#""text""#,
+            r#""text""#,
+            "3",
+            "3.0",
+            "3.5",
+        ].into_iter().map(Value::from_str).collect::<Result<HashSet<Value>, _>>()?;
+        set.insert(Value::from(f64::NAN));
+        set.insert(Value::from(f64::NAN));
+        assert_eq!(set.len(), 6);
+        assert!(set.contains(&Value::from(f64::NAN)));
+        assert!(set.contains(&Value::from(3i64)));
+        Ok(())
+    }
+}
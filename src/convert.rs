@@ -0,0 +1,187 @@
+use crate::error::ConversionError;
+use crate::{Value, ValueMap};
+use crate::prelude::*;
+
+/// Conversion of a Rust value into a [Value].
+pub trait ToValue {
+    /// Create a [Value] representing self.
+    fn to_value(&self) -> Value;
+}
+
+/// Conversion of a [Value] into a Rust value. See [Value::to].
+pub trait FromValue: Sized {
+    /// Convert `value` into Self, or return a [ConversionError] if it has the wrong type.
+    fn from_value(value: &Value) -> Result<Self, ConversionError>;
+}
+
+impl ToValue for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl ToValue for bool {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl ToValue for f64 {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl ToValue for i64 {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl ToValue for u64 {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl ToValue for str {
+    fn to_value(&self) -> Value {
+        Value::from(self)
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value {
+        Value::from(self.as_str())
+    }
+}
+
+impl ToValue for ValueMap {
+    fn to_value(&self) -> Value {
+        Value::Object(self.clone())
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    /// [None] becomes [Value::Null].
+    fn to_value(&self) -> Value {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: ToValue> ToValue for Vec<T> {
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(ToValue::to_value).collect())
+    }
+}
+
+impl<T: ToValue + ?Sized> ToValue for &T {
+    fn to_value(&self) -> Value {
+        (**self).to_value()
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        bool::try_from(value)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        f64::try_from(value)
+    }
+}
+
+impl FromValue for i64 {
+    /// Integral floats are allowed.
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        i64::try_from(value)
+    }
+}
+
+impl FromValue for u64 {
+    /// Integral floats are allowed.
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        value.as_u64().ok_or(ConversionError { expected: "unsigned integer", found: value.type_name() })
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        String::try_from(value)
+    }
+}
+
+impl FromValue for ValueMap {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        value.as_object().cloned().ok_or(ConversionError { expected: "object", found: value.type_name() })
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    /// [Value::Null] becomes [None].
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Array(array) => array.iter().map(T::from_value).collect(),
+            value => Err(ConversionError { expected: "array", found: value.type_name() }),
+        }
+    }
+}
+
+impl Value {
+    /// Convert self into any type implementing [FromValue].
+    /// ```
+    /// # use bourne::Value;
+    /// let value = Value::array().with_item(1i64).with_item(Value::Null);
+    /// assert_eq!(value.to::<Vec<Option<i64>>>(), Ok(vec![Some(1), None]));
+    /// ```
+    pub fn to<T: FromValue>(&self) -> Result<T, ConversionError> {
+        T::from_value(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::error::ConversionError;
+    use crate::{ToValue, Value};
+
+    #[test]
+    fn to_value_test() -> Result<(), crate::error::ParseError> {
+        let numbers = vec![Some(1i64), None, Some(-3)];
+        let value = numbers.to_value();
+        assert_eq!(value, Value::from_str("[1, null, -3]")?);
+        assert_eq!(value.to::<Vec<Option<i64>>>(), Ok(numbers));
+        assert_eq!(
+            value.to::<Vec<i64>>(),
+            Err(ConversionError { expected: "integer", found: "null" }),
+        );
+        assert_eq!(
+            Value::from("x").to::<Vec<String>>(),
+            Err(ConversionError { expected: "array", found: "string" }),
+        );
+        assert_eq!(vec!["a", "b"].to_value().to::<Vec<String>>(), Ok(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(Value::from(2.0).to::<u64>(), Ok(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::pointer::escape_pointer_token;
+use crate::Value;
+use crate::prelude::*;
+
+/// A single difference between two [Value]s, found by [Value::diff].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// JSON Pointer (RFC 6901) to the differing value.
+    pub path: String,
+    /// What changed at `path`.
+    pub kind: DiffKind,
+}
+
+/// The kind of a [Difference].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The value only exists in the new [Value].
+    Added(Value),
+    /// The value only exists in the old [Value].
+    Removed(Value),
+    /// The value exists in both, but differs.
+    Changed {
+        old: Value,
+        new: Value,
+    },
+}
+
+fn diff_into(old: &Value, new: &Value, path: &mut String, differences: &mut Vec<Difference>) {
+    match (old, new) {
+        (Value::Object(old_object), Value::Object(new_object)) => {
+            let mut keys = old_object.keys().chain(new_object.keys().filter(|key| !old_object.contains_key(*key))).collect::<Vec<_>>();
+            keys.sort_unstable();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_pointer_token(key));
+                match (old_object.get(key), new_object.get(key)) {
+                    (Some(old), Some(new)) => diff_into(old, new, path, differences),
+                    (Some(old), None) => differences.push(Difference { path: path.clone(), kind: DiffKind::Removed(old.clone()) }),
+                    (None, Some(new)) => differences.push(Difference { path: path.clone(), kind: DiffKind::Added(new.clone()) }),
+                    (None, None) => unreachable!(),
+                }
+                path.truncate(len);
+            }
+        }
+        (Value::Array(old_array), Value::Array(new_array)) => {
+            for index in 0..old_array.len().max(new_array.len()) {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&index.to_string());
+                match (old_array.get(index), new_array.get(index)) {
+                    (Some(old), Some(new)) => diff_into(old, new, path, differences),
+                    (Some(old), None) => differences.push(Difference { path: path.clone(), kind: DiffKind::Removed(old.clone()) }),
+                    (None, Some(new)) => differences.push(Difference { path: path.clone(), kind: DiffKind::Added(new.clone()) }),
+                    (None, None) => unreachable!(),
+                }
+                path.truncate(len);
+            }
+        }
+        (old, new) if old != new => differences.push(Difference {
+            path: path.clone(),
+            kind: DiffKind::Changed { old: old.clone(), new: new.clone() },
+        }),
+        _ => (),
+    }
+}
+
+impl Value {
+    /// Find the differences between self (the old value) and `other` (the new value).
+    ///
+    /// Objects are compared key by key (in sorted key order) and arrays index by index.
+    /// Any other mismatch, including a change of type, is reported as [DiffKind::Changed].
+    /// Equality follows [PartialEq] for [Value], so `1` and `1.0` are not a difference.
+    pub fn diff(&self, other: &Value) -> Vec<Difference> {
+        let mut differences = Vec::new();
+        diff_into(self, other, &mut String::new(), &mut differences);
+        differences
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn diff_test() -> Result<(), crate::error::ParseError> {
+        let old = Value::from_str(r#"
+            {
+                "name": "Fred",
+                "server": { "port": 80, "hosts": ["a", "b", "c"] },
+                "a/b": 1,
+                "removed": true
+            }
+        "#)?;
+        let new = Value::from_str(r#"
+            {
+                "name": "Fred",
+                "server": { "port": 8080, "hosts": ["a", "x", "c", "d"], "tls": false },
+                "a/b": 1.0
+            }
+        "#)?;
+        let differences = old.diff(&new);
+        assert_eq!(differences, [
+            Difference { path: "/removed".into(), kind: DiffKind::Removed(Value::from(true)) },
+            Difference { path: "/server/hosts/1".into(), kind: DiffKind::Changed { old: "b".into(), new: "x".into() } },
+            Difference { path: "/server/hosts/3".into(), kind: DiffKind::Added("d".into()) },
+            Difference { path: "/server/port".into(), kind: DiffKind::Changed { old: 80i64.into(), new: 8080i64.into() } },
+            Difference { path: "/server/tls".into(), kind: DiffKind::Added(false.into()) },
+        ]);
+        assert!(new.diff(&new).is_empty());
+        assert_eq!(Value::Null.diff(&Value::from(1i64))[0].path, "");
+        Ok(())
+    }
+}
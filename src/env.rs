@@ -0,0 +1,93 @@
+use crate::Value;
+use crate::prelude::*;
+
+/// Convert a path segment into an environment variable name segment.
+/// ASCII letters are uppercased and anything other than an ASCII letter or digit becomes `_`.
+fn env_segment(segment: &str) -> String {
+    segment.chars().map(|c| {
+        if c.is_ascii_alphanumeric() {
+            c.to_ascii_uppercase()
+        } else {
+            '_'
+        }
+    }).collect()
+}
+
+fn join_env_name(prefix: &str, segment: &str) -> String {
+    if prefix.is_empty() {
+        env_segment(segment)
+    } else {
+        format!("{prefix}_{}", env_segment(segment))
+    }
+}
+
+fn collect_env_vars(value: &Value, name: String, vars: &mut Vec<(String, String)>) {
+    match value {
+        Value::Null => vars.push((name, String::new())),
+        Value::Boolean(boolean) => vars.push((name, boolean.to_string())),
+        Value::Number(number) => vars.push((name, number.to_string())),
+        Value::String(string) => vars.push((name, string.clone())),
+        Value::Array(array) => array.iter().enumerate().for_each(|(index, value)| {
+            collect_env_vars(value, join_env_name(&name, &index.to_string()), vars);
+        }),
+        Value::Object(object) => object.iter().for_each(|(key, value)| {
+            collect_env_vars(value, join_env_name(&name, key), vars);
+        }),
+    }
+}
+
+impl Value {
+    /// Flatten a [Value::Object] into environment variable `(name, value)` pairs.
+    ///
+    /// Names are the uppercased path segments joined by `_` and prefixed with `prefix`
+    /// (when not empty), so `{"db": {"host": "x"}}` with prefix `APP` becomes `APP_DB_HOST=x`.
+    /// Array elements use their index as the segment (`APP_TAGS_0`). Strings are emitted
+    /// verbatim, [Value::Null] becomes an empty string, and other scalars use their JSON text.
+    ///
+    /// The pairs are sorted by name. Returns [None] if self is not a [Value::Object].
+    pub fn to_env_vars(&self, prefix: &str) -> Option<Vec<(String, String)>> {
+        let Value::Object(_) = self else {
+            return None;
+        };
+        let mut vars = Vec::new();
+        collect_env_vars(self, env_segment(prefix), &mut vars);
+        vars.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        Some(vars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::Value;
+
+    #[test]
+    fn to_env_vars_test() -> Result<(), crate::error::ParseError> {
+        let config = Value::from_str(r#"
+            {
+                "database": { "host": "db.local", "port": 5432, "pool-size": 1.5 },
+                "debug": true,
+                "log": null,
+                "tags": ["web", "api"]
+            }
+        "#)?;
+        let vars = config.to_env_vars("app").unwrap();
+        let expected = [
+            ("APP_DATABASE_HOST", "db.local"),
+            ("APP_DATABASE_POOL_SIZE", "1.5"),
+            ("APP_DATABASE_PORT", "5432"),
+            ("APP_DEBUG", "true"),
+            ("APP_LOG", ""),
+            ("APP_TAGS_0", "web"),
+            ("APP_TAGS_1", "api"),
+        ];
+        assert_eq!(vars.len(), expected.len());
+        vars.iter().zip(expected).for_each(|((name, value), (expected_name, expected_value))| {
+            assert_eq!(name, expected_name);
+            assert_eq!(value, expected_value);
+        });
+        assert!(Value::from("scalar").to_env_vars("APP").is_none());
+        Ok(())
+    }
+}
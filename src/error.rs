@@ -1,29 +1,406 @@
 use thiserror::Error;
+use crate::prelude::*;
+
+/// A location in the JSON text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Byte offset from the start of the text.
+    pub index: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Position {
+    /// Locate the byte offset `index` in `source`.
+    pub fn locate(source: &str, index: usize) -> Self {
+        let before = &source.as_bytes()[..index.min(source.len())];
+        let line_start = before.iter().rposition(|&b| b == b'\n').map_or(0, |newline| newline + 1);
+        // Count characters by skipping UTF-8 continuation bytes.
+        let column = before[line_start..].iter().filter(|&&b| b & 0xC0 != 0x80).count() + 1;
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        Self { index, line, column }
+    }
+}
+
+impl core::fmt::Display for Position {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}, column {} (index {})", self.line, self.column, self.index)
+    }
+}
+
+/// Coarse classification of a [ParseError], returned by [ParseError::kind].
+///
+/// New [ParseError] variants map onto an existing kind where possible, so matching on
+/// this is more stable than matching on the variants themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The text is not well-formed JSON, or breaks a rule set in the parse options.
+    Syntax,
+    /// The input ended before the value was complete.
+    Eof,
+    /// A number literal could not be parsed or represented.
+    Number,
+    /// A string contains an invalid escape sequence.
+    Escape,
+    /// A nesting depth or input size limit was exceeded.
+    Depth,
+    /// Reading the input failed.
+    Io,
+}
 
 #[derive(Debug, Error)]
 pub enum ParseError {
     /// Invalid character found in the JSON text while parsing.
     #[error("Invalid character at index {0}.")]
     InvalidCharacter(usize),
+    /// A different character was expected in the JSON text while parsing.
+    #[error("Expected {expected} but found '{found}' at index {position}.")]
+    Expected {
+        /// Description of what was expected, such as `"',' or ']'"`.
+        expected: &'static str,
+        /// The character that was found instead.
+        found: char,
+        /// Byte offset of `found`.
+        position: usize,
+    },
     /// Unexpectedly reached the end of the stream.
     #[error("Unexpected end of stream.")]
     UnexpectedEOF,
     /// Unexpectedly reached the end of the stream while parsing a [String].
-    #[error("Unexpected end of stream while parsing string; Start Index: {0}")]
-    UnexpectedEOFWhileParsingString(usize),
+    #[error("Unexpected end of stream at {position} while parsing string starting at {start}.")]
+    UnexpectedEOFWhileParsingString {
+        /// Position of the opening quote.
+        start: Position,
+        /// Position of the end of the stream.
+        position: Position,
+    },
     /// Line break was found while parsing [String]. End quotes must be on the same line.
-    #[error("Line Break while parsing string. End quote must be on same line. Index: {0}")]
-    LineBreakWhileParsingString(usize),
+    #[error("Line break at {position} while parsing string starting at {start}. End quote must be on same line.")]
+    LineBreakWhileParsingString {
+        /// Position of the opening quote.
+        start: Position,
+        /// Position of the line break.
+        position: Position,
+    },
     /// Error parsing integer.
     #[error("Parse Int Error: {0}")]
-    ParseIntError(#[from]std::num::ParseIntError),
+    ParseIntError(#[from]core::num::ParseIntError),
     /// Error parsing floating point number.
     #[error("Parse Float Error: {0}")]
-    ParseFloatError(#[from]std::num::ParseFloatError),
-    /// Invalid escape sequence in [String].
-    #[error("Invalid escape sequence.")]
-    InvalidEscapeSequence,
+    ParseFloatError(#[from]core::num::ParseFloatError),
+    /// A number literal at the given index is too large to be represented as an [f64].
+    #[error("Number out of range at index {0}.")]
+    NumberOutOfRange(usize),
+    /// Invalid escape sequence in [String]. Holds the index of the backslash.
+    #[error("Invalid escape sequence at index {0}.")]
+    InvalidEscapeSequence(usize),
+    /// An unescaped control character (below U+0020) was found in a [String], and
+    /// [ParseOptions::strict_control_chars](crate::parse::ParseOptions::strict_control_chars) is set.
+    #[error("Unescaped control character in string at index {0}.")]
+    ControlCharacterInString(usize),
     /// Invalid hexadecimal value.
     #[error("Invalid Hex.")]
     InvalidHex,
-}
\ No newline at end of file
+    /// The root value is not an array or an object, and
+    /// [ParseOptions::require_compound_root](crate::parse::ParseOptions::require_compound_root) is set.
+    #[error("The root value must be an array or an object.")]
+    NonCompoundRoot,
+    /// The input bytes are not valid UTF-8.
+    #[error("Invalid UTF-8: {0}")]
+    InvalidUtf8(#[from]core::str::Utf8Error),
+    /// An object has the same key more than once, and
+    /// [ParseOptions::on_duplicate_key](crate::parse::ParseOptions::on_duplicate_key) is
+    /// [DuplicateKeyStrategy::Reject](crate::parse::DuplicateKeyStrategy::Reject).
+    #[error("Duplicate key {key:?} at index {position}.")]
+    DuplicateKey {
+        /// The repeated key.
+        key: String,
+        /// Byte offset of the second occurrence of the key.
+        position: usize,
+    },
+    /// The input is longer than
+    /// [ParseOptions::max_input_bytes](crate::parse::ParseOptions::max_input_bytes).
+    #[error("Input of {size} bytes exceeds the limit of {limit} bytes.")]
+    InputTooLarge {
+        /// The length of the input in bytes.
+        size: usize,
+        /// The configured limit.
+        limit: usize,
+    },
+    /// An array or object at the given index is nested deeper than
+    /// [ParseOptions::max_depth](crate::parse::ParseOptions::max_depth).
+    #[error("Nesting depth limit exceeded at index {0}.")]
+    DepthLimitExceeded(usize),
+    /// Reading the input failed.
+    #[cfg(feature = "std")]
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Error in one of the values of a sequence parsed by
+    /// [Value::from_ndjson](crate::Value::from_ndjson) or
+    /// [Value::stream_array_elements](crate::Value::stream_array_elements).
+    #[error("Error in record {record}: {error}")]
+    Record {
+        /// The number of the failing record, starting at 1.
+        record: usize,
+        /// The error. Indices in it are relative to the whole input for
+        /// [Value::from_ndjson](crate::Value::from_ndjson), and to the start of the
+        /// element for [Value::stream_array_elements](crate::Value::stream_array_elements).
+        #[source]
+        error: Box<ParseError>,
+    },
+}
+
+impl ParseError {
+    /// A [ParseError::InvalidCharacter] at byte offset `index`.
+    pub fn invalid_character_at(index: usize) -> Self {
+        ParseError::InvalidCharacter(index)
+    }
+
+    /// A [ParseError::Expected] for the character at byte offset `index` of `source`, or
+    /// [ParseError::UnexpectedEOF] if `index` is at or past the end.
+    pub fn expected_at(expected: &'static str, source: &str, index: usize) -> Self {
+        // `index` should be at a character boundary, but fall back to the raw byte just in case.
+        let found = source.get(index..)
+            .and_then(|rest| rest.chars().next())
+            .or_else(|| source.as_bytes().get(index).map(|&b| b as char));
+        match found {
+            Some(found) => ParseError::Expected { expected, found, position: index },
+            None => ParseError::UnexpectedEOF,
+        }
+    }
+
+    /// A [ParseError::UnexpectedEOFWhileParsingString] for a string whose opening quote is
+    /// at byte offset `start` of `source`.
+    pub fn unterminated_string_at(source: &str, start: usize) -> Self {
+        ParseError::UnexpectedEOFWhileParsingString {
+            start: Position::locate(source, start),
+            position: Position::locate(source, source.len()),
+        }
+    }
+
+    /// A [ParseError::LineBreakWhileParsingString] for a line break at byte offset `index`
+    /// in a string whose opening quote is at byte offset `start` of `source`.
+    pub fn line_break_in_string_at(source: &str, start: usize, index: usize) -> Self {
+        ParseError::LineBreakWhileParsingString {
+            start: Position::locate(source, start),
+            position: Position::locate(source, index),
+        }
+    }
+
+    /// Classify the error. See [ErrorKind].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ParseError::InvalidCharacter(_)
+            | ParseError::Expected { .. }
+            | ParseError::LineBreakWhileParsingString { .. }
+            | ParseError::ControlCharacterInString(_)
+            | ParseError::NonCompoundRoot
+            | ParseError::DuplicateKey { .. }
+            | ParseError::InvalidUtf8(_) => ErrorKind::Syntax,
+            ParseError::UnexpectedEOF
+            | ParseError::UnexpectedEOFWhileParsingString { .. } => ErrorKind::Eof,
+            ParseError::ParseIntError(_)
+            | ParseError::ParseFloatError(_)
+            | ParseError::NumberOutOfRange(_) => ErrorKind::Number,
+            ParseError::InvalidEscapeSequence(_)
+            | ParseError::InvalidHex => ErrorKind::Escape,
+            ParseError::InputTooLarge { .. }
+            | ParseError::DepthLimitExceeded(_) => ErrorKind::Depth,
+            #[cfg(feature = "std")]
+            ParseError::Io(_) => ErrorKind::Io,
+            ParseError::Record { error, .. } => error.kind(),
+        }
+    }
+
+    /// The byte offset in the input where the error was found, if the error has one.
+    /// For [ParseError::Record], this is the offset of the inner error.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            ParseError::InvalidCharacter(index)
+            | ParseError::NumberOutOfRange(index)
+            | ParseError::InvalidEscapeSequence(index)
+            | ParseError::ControlCharacterInString(index)
+            | ParseError::DepthLimitExceeded(index)
+            | ParseError::Expected { position: index, .. }
+            | ParseError::DuplicateKey { position: index, .. } => Some(*index),
+            ParseError::UnexpectedEOFWhileParsingString { position, .. }
+            | ParseError::LineBreakWhileParsingString { position, .. } => Some(position.index),
+            ParseError::InvalidUtf8(error) => Some(error.valid_up_to()),
+            ParseError::Record { error, .. } => error.offset(),
+            _ => None,
+        }
+    }
+
+    /// Attach the line of `source` the error points at, for a diagnostic showing a caret
+    /// under the offending character. `source` must be the text that was parsed;
+    /// [ParseError::UnexpectedEOF] points at its end.
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use bourne::Value;
+    /// let source = "[1,\n 2 x]";
+    /// let error = Value::from_str(source).unwrap_err().with_source_context(source);
+    /// assert!(error.to_string().ends_with("2 |  2 x]\n  |    ^"));
+    /// ```
+    pub fn with_source_context(self, source: &str) -> DetailedError {
+        let offset = match self {
+            ParseError::UnexpectedEOF => Some(source.len()),
+            _ => self.offset(),
+        };
+        let Some(offset) = offset else {
+            return DetailedError { error: self, position: None, line: String::new(), caret: String::new() };
+        };
+        let position = Position::locate(source, offset);
+        // The offset may not be a character boundary in `source` if it's the wrong text.
+        let line_start = source.as_bytes()[..offset.min(source.len())].iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |newline| newline + 1);
+        let line_end = source[line_start..].find('\n').map_or(source.len(), |newline| line_start + newline);
+        let line = source[line_start..line_end].trim_end_matches('\r');
+        // Keep tabs, so the caret lines up however wide they are shown.
+        let caret = line.chars()
+            .take(position.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        DetailedError { error: self, position: Some(position), line: line.to_owned(), caret }
+    }
+}
+
+/// A [ParseError] together with the line of input it points at, from
+/// [ParseError::with_source_context]. Displayed like a compiler diagnostic:
+/// ```text
+/// Expected ',' or ']' but found 'x' at index 7.
+///  --> line 2, column 4 (index 7)
+///   |
+/// 2 |  2 x]
+///   |    ^
+/// ```
+/// Errors without an offset are displayed as just the message.
+#[derive(Debug)]
+pub struct DetailedError {
+    error: ParseError,
+    position: Option<Position>,
+    line: String,
+    /// Whitespace up to the error's column.
+    caret: String,
+}
+
+impl DetailedError {
+    /// The underlying error.
+    pub fn error(&self) -> &ParseError {
+        &self.error
+    }
+
+    /// Where the error was found, if it has an offset.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Unwrap the underlying error.
+    pub fn into_error(self) -> ParseError {
+        self.error
+    }
+}
+
+impl core::fmt::Display for DetailedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.error)?;
+        let Some(position) = self.position else {
+            return Ok(());
+        };
+        let number = position.line.to_string();
+        let gutter = " ".repeat(number.len());
+        writeln!(f)?;
+        writeln!(f, "{gutter}--> {position}")?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{number} | {}", self.line)?;
+        write!(f, "{gutter} | {}^", self.caret)
+    }
+}
+
+impl core::error::Error for DetailedError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Error converting a [Value](crate::Value) into a Rust type.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("Expected {expected}, found {found}.")]
+pub struct ConversionError {
+    /// The kind of value that was expected.
+    pub expected: &'static str,
+    /// The type of the [Value](crate::Value) that was found.
+    pub found: &'static str,
+}
+
+/// Error rebuilding a [Value](crate::Value) with [Value::unflatten](crate::Value::unflatten).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum UnflattenError {
+    /// The path is not a valid dotted/bracketed path.
+    #[error("Invalid path: {0:?}")]
+    InvalidPath(String),
+    /// The path needs a container where a scalar was already placed, or the other way around.
+    #[error("Conflicting path: {0:?}")]
+    Conflict(String),
+}
+
+/// Error serializing a [Value](crate::Value) as canonical JSON with
+/// [Value::to_canonical_string](crate::Value::to_canonical_string).
+#[derive(Debug, Error, Clone, Copy, PartialEq)]
+pub enum CanonicalizeError {
+    /// NaN and the infinities have no representation in canonical JSON.
+    #[error("Non-finite number {0} cannot be represented in canonical JSON.")]
+    NonFiniteNumber(f64),
+}
+
+/// Error applying a JSON Patch with [Value::apply_patch](crate::Value::apply_patch).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The path is not a valid JSON Pointer.
+    #[error("Invalid JSON Pointer: {0:?}")]
+    InvalidPointer(String),
+    /// The path, or the container it points into, doesn't exist.
+    #[error("Path not found: {0:?}")]
+    PathNotFound(String),
+    /// A `test` operation found a different value.
+    #[error("Test failed at {0:?}")]
+    TestFailed(String),
+    /// A `move` operation tried to move a value into one of its own children.
+    #[error("Cannot move {from:?} into its own child {path:?}")]
+    InvalidMove { from: String, path: String },
+    /// An operation object in a patch document is malformed.
+    #[error("Invalid patch operation: {0}")]
+    InvalidOperation(String),
+}
+
+/// Error serializing a [Value](crate::Value) with
+/// [Formatter::try_format](crate::format::Formatter::try_format).
+#[derive(Debug, Error, Clone, Copy, PartialEq)]
+pub enum FormatError {
+    /// NaN or an infinity was found with [NonFiniteMode::Error](crate::format::NonFiniteMode::Error).
+    #[error("Non-finite number {0} cannot be represented in JSON.")]
+    NonFiniteNumber(f64),
+}
+
+/// Error encoding a [Value](crate::Value) with
+/// [Value::to_query_string](crate::Value::to_query_string).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum QueryStringError {
+    /// Only objects can be encoded. Holds the type of the value found instead.
+    #[error("Expected object, found {0}.")]
+    NotAnObject(&'static str),
+    /// The value of the key is an array or an object, which has no query string form.
+    #[error("Nested value for key {0:?} cannot be encoded in a query string.")]
+    Nested(String),
+}
+
+/// Error parsing a JSONPath expression in [Value::query](crate::Value::query).
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("Invalid JSONPath expression at index {position}.")]
+pub struct QueryError {
+    /// Byte offset in the expression where the error was found.
+    pub position: usize,
+}
@@ -0,0 +1,211 @@
+use crate::error::UnflattenError;
+use crate::{Key, Value};
+use crate::prelude::*;
+
+/// A segment of a flattened path.
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Split a flattened path like `a.b[0]` into its segments.
+fn path_segments(path: &str) -> Option<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    // The first key has no leading `.`, and is absent if the root is an array.
+    let first_key_len = path.find(['.', '[']).unwrap_or(path.len());
+    if first_key_len > 0 {
+        segments.push(Segment::Key(&path[..first_key_len]));
+    } else if path.starts_with('.') {
+        return None;
+    }
+    let mut rest = &path[first_key_len..];
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let key_len = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            if key_len == 0 {
+                return None;
+            }
+            segments.push(Segment::Key(&after_dot[..key_len]));
+            rest = &after_dot[key_len..];
+        } else {
+            let (index, after_index) = rest.strip_prefix('[')?.split_once(']')?;
+            if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            segments.push(Segment::Index(index.parse().ok()?));
+            rest = after_index;
+        }
+    }
+    Some(segments)
+}
+
+fn collect_leaves<'a>(value: &'a Value, path: String, leaves: &mut Vec<(String, &'a Value)>) {
+    match value {
+        Value::Array(array) => array.iter().enumerate().for_each(|(index, value)| {
+            collect_leaves(value, format!("{path}[{index}]"), leaves);
+        }),
+        Value::Object(object) => object.iter().for_each(|(key, value)| {
+            let path = if path.is_empty() { key.to_string() } else { format!("{path}.{key}") };
+            collect_leaves(value, path, leaves);
+        }),
+        _ => leaves.push((path, value)),
+    }
+}
+
+impl Value {
+    /// Collapse the tree into `(path, leaf)` pairs, one for each scalar in the value.
+    ///
+    /// Object keys are joined by `.` and array elements use `[index]`, so
+    /// `{"a": {"b": [true]}}` becomes `[("a.b[0]", true)]`. Empty arrays and objects
+    /// have no leaves and are left out. A scalar root has the path `""`. Keys are not
+    /// escaped, so keys containing `.`, `[` or `]` produce ambiguous paths.
+    ///
+    /// Array elements are listed in order. Object entries follow the iteration order of
+    /// the map.
+    pub fn flatten(&self) -> Vec<(String, &Value)> {
+        let mut leaves = Vec::new();
+        collect_leaves(self, String::new(), &mut leaves);
+        leaves
+    }
+
+    /// Rebuild a tree from `(path, value)` pairs, the inverse of [Value::flatten].
+    ///
+    /// `a.b` creates an object `a` with the key `b`, and `a[1]` creates an array `a`
+    /// padded with [Value::Null] up to index 1. Placing a value where another
+    /// non-container value already is replaces it.
+    ///
+    /// An array can't have more elements than there are pairs, so an index that is not
+    /// less than the number of pairs is rejected as [UnflattenError::InvalidPath]
+    /// rather than padding an array to that length. This keeps every output of
+    /// [Value::flatten] valid, whatever order its pairs come in.
+    ///
+    /// Returns [UnflattenError::InvalidPath] for malformed paths, and
+    /// [UnflattenError::Conflict] if a path requires an object where there is an array
+    /// or a scalar, or the other way around.
+    pub fn unflatten(pairs: impl IntoIterator<Item = (String, Value)>) -> Result<Value, UnflattenError> {
+        let pairs = pairs.into_iter().collect::<Vec<_>>();
+        let max_len = pairs.len();
+        let mut root = Value::Null;
+        for (path, value) in pairs {
+            let Some(segments) = path_segments(&path) else {
+                return Err(UnflattenError::InvalidPath(path));
+            };
+            let mut node = &mut root;
+            for segment in segments {
+                if let Value::Null = node {
+                    *node = match segment {
+                        Segment::Key(_) => Value::object(),
+                        Segment::Index(_) => Value::array(),
+                    };
+                }
+                node = match (segment, node) {
+                    (Segment::Key(key), Value::Object(object)) => {
+                        object.entry(Key::from(key)).or_insert(Value::Null)
+                    }
+                    (Segment::Index(index), Value::Array(_)) if index >= max_len => {
+                        return Err(UnflattenError::InvalidPath(path));
+                    }
+                    (Segment::Index(index), Value::Array(array)) => {
+                        if array.len() <= index {
+                            array.resize(index + 1, Value::Null);
+                        }
+                        &mut array[index]
+                    }
+                    _ => return Err(UnflattenError::Conflict(path)),
+                };
+            }
+            if matches!(node, Value::Array(_) | Value::Object(_)) {
+                return Err(UnflattenError::Conflict(path));
+            }
+            *node = value;
+        }
+        Ok(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::error::UnflattenError;
+    use crate::Value;
+
+    #[test]
+    fn flatten_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "tag": null,
+                "registered": true,
+                "age": 197,
+                "name": "Fred",
+                "classes": [
+                    "Algebra",
+                    "History of Programming",
+                    "Algorithms and Datastructures",
+                    "Cryptography"
+                ],
+                "rgb_for_some_reason": {
+                    "r": 4,
+                    "g": 7,
+                    "b": 3
+                },
+                "empty": {}
+            }
+        "#)?;
+        let mut flat = value.flatten();
+        flat.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        let expected = [
+            ("age", Value::from(197i64)),
+            ("classes[0]", Value::from("Algebra")),
+            ("classes[1]", Value::from("History of Programming")),
+            ("classes[2]", Value::from("Algorithms and Datastructures")),
+            ("classes[3]", Value::from("Cryptography")),
+            ("name", Value::from("Fred")),
+            ("registered", Value::from(true)),
+            ("rgb_for_some_reason.b", Value::from(3i64)),
+            ("rgb_for_some_reason.g", Value::from(7i64)),
+            ("rgb_for_some_reason.r", Value::from(4i64)),
+            ("tag", Value::Null),
+        ];
+        assert_eq!(flat.len(), expected.len());
+        flat.iter().zip(expected).for_each(|((path, value), (expected_path, expected_value))| {
+            assert_eq!(path, expected_path);
+            assert_eq!(**value, expected_value);
+        });
+        let scalar = Value::from(1i64);
+        assert_eq!(scalar.flatten(), vec![(String::new(), &scalar)]);
+        Ok(())
+    }
+
+    #[test]
+    fn unflatten_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "tag": null,
+                "registered": true,
+                "age": 197,
+                "name": "Fred",
+                "classes": ["Algebra", "History of Programming", "Algorithms and Datastructures"],
+                "rgb_for_some_reason": { "r": 4, "g": 7, "b": 3 },
+                "matrix": [[1, 2], [3]]
+            }
+        "#)?;
+        let pairs = value.flatten().into_iter().map(|(path, leaf)| (path, leaf.clone()));
+        assert_eq!(Value::unflatten(pairs), Ok(value));
+
+        let pairs = |pairs: &[(&str, i64)]| pairs.iter()
+            .map(|&(path, value)| (path.to_string(), Value::from(value)))
+            .collect::<Vec<_>>();
+        assert_eq!(Value::unflatten(pairs(&[("a.b", 1), ("a.c", 2)])), Ok(Value::from_str(r#"{"a": {"b": 1, "c": 2}}"#)?));
+        assert_eq!(Value::unflatten(pairs(&[("a[1]", 2), ("a[0]", 1)])), Ok(Value::from_str(r#"{"a": [1, 2]}"#)?));
+        assert_eq!(Value::unflatten(pairs(&[("[0].x", 1)])), Ok(Value::from_str(r#"[{"x": 1}]"#)?));
+        assert_eq!(Value::unflatten(pairs(&[("", 1)])), Ok(Value::from(1i64)));
+        assert_eq!(Value::unflatten(pairs(&[("a", 1), ("a.b", 2)])), Err(UnflattenError::Conflict("a.b".into())));
+        assert_eq!(Value::unflatten(pairs(&[("a.b", 2), ("a", 1)])), Err(UnflattenError::Conflict("a".into())));
+        assert_eq!(Value::unflatten(pairs(&[("a.b", 2), ("a[0]", 1)])), Err(UnflattenError::Conflict("a[0]".into())));
+        for invalid in ["a..b", ".a", "a.", "a[", "a[x]", "a[]", "a[0]b", "a[1]", "a[99999999999]", "a[18446744073709551615]"] {
+            assert_eq!(Value::unflatten(pairs(&[(invalid, 1)])), Err(UnflattenError::InvalidPath(invalid.into())));
+        }
+        Ok(())
+    }
+}
@@ -1,23 +1,29 @@
 #![allow(unused)]
 
 use core::str;
-use std::fmt::{
-    Write,
-    Formatter,
-};
-use std::str::FromStr;
+use core::fmt::Write;
+use core::str::FromStr;
 
 use crate::{error::*, Number};
 use crate::{
+    Key,
     Value,
     ValueMap,
 };
-
-impl std::fmt::Display for Number {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+use crate::prelude::*;
+
+impl core::fmt::Display for Number {
+    /// Integers are written without a decimal point. Floats are written in their
+    /// shortest round-trippable form and always keep a decimal point or exponent
+    /// (`3.0`, `1e-7`), so they parse back as [Number::Float]. [Number::Raw] is
+    /// written verbatim.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            &Number::Float(float) => write!(f, "{}", float),
-            &Number::Int(int) => write!(f, "{}", int),
+            // The Debug implementation for f64 is the one that keeps the `.0`.
+            Number::Float(float) => write!(f, "{:?}", float),
+            Number::Int(int) => write!(f, "{}", int),
+            Number::Uint(uint) => write!(f, "{}", uint),
+            Number::Raw(text) => write!(f, "{}", text),
         }
     }
 }
@@ -28,15 +34,29 @@ pub enum Indent {
     Tabs(u8),
 }
 
-impl std::fmt::Display for Indent {
-    /// Writes an [Indent] to a [Formatter]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl From<&str> for Indent {
+    /// An indentation string such as `"  "` or `"\t"`. A string of tabs becomes
+    /// [Indent::Tabs], anything else [Indent::Spaces] of the same length in characters.
+    /// The count saturates at 255.
+    fn from(indent: &str) -> Self {
+        let count = indent.chars().count().min(u8::MAX as usize) as u8;
+        if !indent.is_empty() && indent.bytes().all(|byte| byte == b'\t') {
+            Self::Tabs(count)
+        } else {
+            Self::Spaces(count)
+        }
+    }
+}
+
+impl core::fmt::Display for Indent {
+    /// Writes an [Indent] to a [std::fmt::Formatter]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // SAFETY: Creation of valid utf-8 string from byte array of spaces/tabs.
-        const SPACES: &'static str = unsafe { str::from_utf8_unchecked(&[b' '; 256]) };
-        const TABS: &'static str = unsafe { str::from_utf8_unchecked(&[b'\t'; 256]) };
-        match self {
-            &Self::Spaces(count) => write!(f, "{}", &SPACES[..count as usize]),
-            &Self::Tabs(count) => write!(f, "{}", &TABS[..count as usize]),
+        const SPACES: &str = unsafe { str::from_utf8_unchecked(&[b' '; 256]) };
+        const TABS: &str = unsafe { str::from_utf8_unchecked(&[b'\t'; 256]) };
+        match *self {
+            Self::Spaces(count) => write!(f, "{}", &SPACES[..count as usize]),
+            Self::Tabs(count) => write!(f, "{}", &TABS[..count as usize]),
         }
     }
 }
@@ -51,12 +71,46 @@ struct JsonFormatter {
     indent: Indent,
     /// Indent level. Only modify this if you know what you're doing.
     indent_level: u32,
+    /// Which optional escapes to use for strings.
+    escape: EscapeOptions,
+    /// Write object keys in lexicographical order.
+    sort_keys: bool,
+    /// Write arrays and objects nested deeper than this compactly, on a single line.
+    collapse_after_depth: Option<usize>,
+    /// How to write NaN and the infinities.
+    non_finite: NonFiniteMode,
+}
+
+/// How a [Formatter] writes a [Number::Float] that is NaN or infinite, which JSON has no
+/// representation for. Set with [Formatter::non_finite].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NonFiniteMode {
+    /// Fail: [Formatter::try_format] returns [FormatError::NonFiniteNumber], and
+    /// [Formatter::write] returns [core::fmt::Error], and [Formatter::format] panics.
+    Error,
+    /// Write `null`, as JavaScript's `JSON.stringify` does. This is the default, and what
+    /// [Display](core::fmt::Display) (and so `to_string`) uses.
+    #[default]
+    Null,
+    /// Write the strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    String,
+}
+
+/// Optional escapes applied when writing strings, for [escape_string_with_options].
+/// [Formatter::escape_solidus] and [Formatter::ascii_only] set these for whole values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EscapeOptions {
+    /// Escape `/` as `\/`. Default: `false`.
+    pub solidus: bool,
+    /// Escape every character above `\u{7f}` as `\uXXXX`, using surrogate pairs where needed.
+    /// Default: `false`.
+    pub ascii_only: bool,
 }
 
 struct Indentation<'a>(&'a JsonFormatter);
 
-impl<'a> std::fmt::Display for Indentation<'a> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Display for Indentation<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         for _ in 0..self.0.indent_level {
             write!(f, "{}", self.0.indent)?;
         }
@@ -74,13 +128,28 @@ impl JsonFormatter {
             sameline,
             spacing,
             indent,
-            indent_level: indent_level,
+            indent_level,
+            escape: EscapeOptions::default(),
+            sort_keys: false,
+            collapse_after_depth: None,
+            non_finite: NonFiniteMode::default(),
+        }
+    }
+
+    /// Creates a copy of self that writes compactly if the current indent level is
+    /// beyond `collapse_after_depth`.
+    fn collapse(&self) -> Self {
+        let mut collapsed = *self;
+        if self.collapse_after_depth.is_some_and(|depth| self.indent_level as usize >= depth) {
+            collapsed.sameline = true;
+            collapsed.spacing = false;
         }
+        collapsed
     }
 
     /// Creates a copy of self where the indent level is incremented by 1.
     fn indent(&self) -> Self {
-        let mut indent = self.clone();
+        let mut indent = *self;
         indent.indent_level += 1;
         indent
     }
@@ -91,14 +160,14 @@ impl JsonFormatter {
     }
 
     /// Writes the indentation to a writer.
-    fn write_indent<W: Write>(&self, writer: &mut W) -> std::fmt::Result {
+    fn write_indent<W: Write>(&self, writer: &mut W) -> core::fmt::Result {
         write!(writer, "{}", self.indentation())
     }
 
-    fn write_separator<W: Write>(&self, writer: &mut W) -> std::fmt::Result {
+    fn write_separator<W: Write>(&self, writer: &mut W) -> core::fmt::Result {
         write!(writer, ",")?;
         if !self.sameline {
-            write!(writer, "\n")?;
+            writeln!(writer)?;
         // Are double-negatives allowed in programming? There's not no spacing here.
         } else if self.spacing {
             write!(writer, " ")?;
@@ -107,8 +176,8 @@ impl JsonFormatter {
     }
 }
 
-impl std::fmt::Display for JsonFormatter {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JsonFormatter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if !self.sameline {
             self.write_indent(f)?;
         }
@@ -154,27 +223,45 @@ pub fn measure_escaped_string<S: AsRef<str>>(s: S) -> usize {
 
 /// Escapes a string.
 pub fn escape_string<S: AsRef<str>>(s: S) -> String {
+    escape_string_with_options(s, EscapeOptions::default())
+}
+
+/// Escapes a string, with the optional escapes in `options`. The result, wrapped in
+/// quotes, is a JSON string that [unescape_string](crate::parse::unescape_string)
+/// turns back into `s`.
+pub fn escape_string_with_options<S: AsRef<str>>(s: S, options: EscapeOptions) -> String {
     let mut buffer = String::with_capacity(measure_escaped_string(s.as_ref()));
     // Writing to a String is infallible (I think), so this should never fail.
-    write_escaped_string(&mut buffer, s).unwrap();
+    write_escaped_string(&mut buffer, s, options).unwrap();
     buffer
 }
 
-fn write_escaped_string<W: Write, S: AsRef<str>>(writer: &mut W, s: S) -> std::fmt::Result {
+/// Writes a `\uXXXX` escape for a single UTF-16 code unit.
+fn write_unicode_escape<W: Write>(writer: &mut W, unit: u16) -> core::fmt::Result {
+    write!(writer, "\\u")?;
+    for i in (0..4).rev() {
+        write!(writer, "{}", hex_char(unit, i, true))?;
+    }
+    Ok(())
+}
+
+/// Writes an escaped string. Control characters without a short escape are written as `\u00XX`.
+fn write_escaped_string<W: Write, S: AsRef<str>>(writer: &mut W, s: S, options: EscapeOptions) -> core::fmt::Result {
     s.as_ref().chars().try_for_each(|c| {
         match c {
             '\\' => write!(writer, "\\\\")?,
             '"' => write!(writer, "\\\"")?,
+            '/' if options.solidus => write!(writer, "\\/")?,
             '\u{000c}' => write!(writer, "\\f")?,
             '\u{0008}' => write!(writer, "\\b")?,
             '\n' => write!(writer, "\\n")?,
             '\r' => write!(writer, "\\r")?,
             '\t' => write!(writer, "\\t")?,
-            '\u{0000}'..='\u{001f}' => {
-                let hex = c as u16;
-                write!(writer, "\\u")?;
-                for i in (0..4).rev() {
-                    write!(writer, "{}", hex_char(hex, i, true))?;
+            '\u{0000}'..='\u{001f}' => write_unicode_escape(writer, c as u16)?,
+            '\u{0080}'.. if options.ascii_only => {
+                let mut units = [0u16; 2];
+                for &unit in c.encode_utf16(&mut units).iter() {
+                    write_unicode_escape(writer, unit)?;
                 }
             }
             _ => write!(writer, "{c}")?,
@@ -183,100 +270,376 @@ fn write_escaped_string<W: Write, S: AsRef<str>>(writer: &mut W, s: S) -> std::f
     })
 }
 
-fn write_null<W: Write>(writer: &mut W) -> std::fmt::Result {
+fn write_null<W: Write>(writer: &mut W) -> core::fmt::Result {
     write!(writer, "null")
 }
 
-fn write_boolean<W: Write>(writer: &mut W, value: bool) -> std::fmt::Result {
+fn write_boolean<W: Write>(writer: &mut W, value: bool) -> core::fmt::Result {
     write!(writer, "{value}")
 }
 
-fn write_number<W: Write>(writer: &mut W, value: Number) -> std::fmt::Result {
-    write!(writer, "{value}")
+/// Why [try_write_value] failed.
+enum WriteError {
+    Fmt(core::fmt::Error),
+    NonFinite(f64),
+}
+
+impl From<core::fmt::Error> for WriteError {
+    fn from(error: core::fmt::Error) -> Self {
+        WriteError::Fmt(error)
+    }
 }
 
-fn write_string<W: Write>(writer: &mut W, value: &str) -> std::fmt::Result {
+/// Writes a [Number]. [Number::Float]s always keep a fractional part or an exponent
+/// (`2.0`, `1e-7`), so that integral floats parse back as floats rather than integers.
+/// NaN and the infinities are written according to [JsonFormatter::non_finite].
+fn write_number<W: Write>(writer: &mut W, value: &Number, formatter: JsonFormatter) -> Result<(), WriteError> {
+    match *value {
+        Number::Float(float) if !float.is_finite() => match formatter.non_finite {
+            NonFiniteMode::Error => return Err(WriteError::NonFinite(float)),
+            NonFiniteMode::Null => write_null(writer)?,
+            NonFiniteMode::String if float.is_nan() => write!(writer, "\"NaN\"")?,
+            NonFiniteMode::String if float > 0.0 => write!(writer, "\"Infinity\"")?,
+            NonFiniteMode::String => write!(writer, "\"-Infinity\"")?,
+        },
+        _ => write!(writer, "{value}")?,
+    }
+    Ok(())
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str, formatter: JsonFormatter) -> core::fmt::Result {
     write!(writer, "\"")?;
-    write_escaped_string(writer, value)?;
+    write_escaped_string(writer, value, formatter.escape)?;
     write!(writer, "\"")
 }
 
-fn write_array<W: Write>(writer: &mut W, array: &[Value], formatter: JsonFormatter) -> std::fmt::Result {
-    write!(writer, "[")?;
+/// A pending step of [write_value].
+enum Frame<'a> {
+    /// Write a whole value.
+    Value(&'a Value, JsonFormatter),
+    /// Write the remaining elements of an array, then close it.
+    Array {
+        elements: core::slice::Iter<'a, Value>,
+        formatter: JsonFormatter,
+        first: bool,
+    },
+    /// Write the remaining entries of an object, then close it.
+    Object {
+        entries: alloc::vec::IntoIter<(&'a Key, &'a Value)>,
+        formatter: JsonFormatter,
+        first: bool,
+    },
+}
+
+/// Writes the opening bracket of an array or object.
+fn write_open<W: Write>(writer: &mut W, bracket: char, formatter: JsonFormatter) -> core::fmt::Result {
+    write!(writer, "{bracket}")?;
     if !formatter.sameline {
-        write!(writer, "\n")?;
+        writeln!(writer)?;
     }
-    let indented_formatter = formatter.indent();
-    array.iter().enumerate().try_for_each(|(index, value)| {
-        if !indented_formatter.sameline {
-            write!(writer, "{}", indented_formatter.indentation())?;
-        }
-        write_value(writer, value, indented_formatter)?;
-        // Make sure it's not the final item.
-        if index + 1 != array.len() {
-            indented_formatter.write_separator(writer)?;
-        }
-        Ok(())
-    })?;
+    Ok(())
+}
+
+/// Writes the closing bracket of an array or object.
+fn write_close<W: Write>(writer: &mut W, bracket: char, formatter: JsonFormatter) -> core::fmt::Result {
     if !formatter.sameline {
-        write!(writer, "\n")?;
+        writeln!(writer)?;
         write!(writer, "{}", formatter.indentation())?;
     }
-    
-    write!(writer, "]")
+    write!(writer, "{bracket}")
 }
 
-fn write_object<W: Write>(writer: &mut W, object: &ValueMap, formatter: JsonFormatter) -> std::fmt::Result {
-    write!(writer, "{{")?;
-    if !formatter.sameline {
-        write!(writer, "\n")?;
+/// Writes the separator (unless this is the first item) and indentation before an array
+/// element or object entry. `indent` is the formatter of the items.
+fn write_item_prefix<W: Write>(writer: &mut W, indent: JsonFormatter, first: bool) -> core::fmt::Result {
+    if !first {
+        indent.write_separator(writer)?;
     }
-    let indent = formatter.indent();
-    object.iter().enumerate().try_for_each(|(index, (key, value))| {
-        if !indent.sameline {
-            write!(writer, "{}", indent.indentation())?;
-        }
-        write_string(writer, key)?;
-        if indent.spacing {
-            write!(writer, " : ")?;
-        } else {
-            write!(writer, ":")?;
+    if !indent.sameline {
+        write!(writer, "{}", indent.indentation())?;
+    }
+    Ok(())
+}
+
+/// Writes a [Value], failing with [core::fmt::Error] for non-finite numbers under
+/// [NonFiniteMode::Error].
+fn write_value<W: Write>(writer: &mut W, value: &Value, formatter: JsonFormatter) -> core::fmt::Result {
+    try_write_value(writer, value, formatter).map_err(|_| core::fmt::Error)
+}
+
+/// Writes a [Value]. Nested arrays and objects are handled with an explicit stack instead
+/// of recursion, so the nesting depth is limited by the heap rather than the call stack.
+fn try_write_value<W: Write>(writer: &mut W, value: &Value, formatter: JsonFormatter) -> Result<(), WriteError> {
+    let mut stack = vec![Frame::Value(value, formatter)];
+    while let Some(frame) = stack.pop() {
+        match frame {
+            Frame::Value(value, formatter) => match value {
+                Value::Null => write_null(writer)?,
+                &Value::Boolean(boolean) => write_boolean(writer, boolean)?,
+                Value::Number(number) => write_number(writer, number, formatter)?,
+                Value::String(string) => write_string(writer, string, formatter)?,
+                Value::Array(array) => {
+                    let formatter = formatter.collapse();
+                    write_open(writer, '[', formatter)?;
+                    stack.push(Frame::Array { elements: array.iter(), formatter, first: true });
+                }
+                Value::Object(object) => {
+                    let formatter = formatter.collapse();
+                    write_open(writer, '{', formatter)?;
+                    let mut entries = object.iter().collect::<Vec<_>>();
+                    if formatter.sort_keys {
+                        entries.sort_unstable_by_key(|(key, _)| *key);
+                    }
+                    stack.push(Frame::Object { entries: entries.into_iter(), formatter, first: true });
+                }
+            },
+            Frame::Array { mut elements, formatter, first } => match elements.next() {
+                Some(element) => {
+                    let indent = formatter.indent();
+                    write_item_prefix(writer, indent, first)?;
+                    stack.push(Frame::Array { elements, formatter, first: false });
+                    stack.push(Frame::Value(element, indent));
+                }
+                None => write_close(writer, ']', formatter)?,
+            },
+            Frame::Object { mut entries, formatter, first } => match entries.next() {
+                Some((key, value)) => {
+                    let indent = formatter.indent();
+                    write_item_prefix(writer, indent, first)?;
+                    write_string(writer, key, indent)?;
+                    if indent.spacing {
+                        write!(writer, " : ")?;
+                    } else {
+                        write!(writer, ":")?;
+                    }
+                    stack.push(Frame::Object { entries, formatter, first: false });
+                    stack.push(Frame::Value(value, indent));
+                }
+                None => write_close(writer, '}', formatter)?,
+            },
         }
-        write_value(writer, value, indent)?;
-        // Make sure it's not the final item
-        if index + 1 != object.len() {
-            indent.write_separator(writer)?;
+    }
+    Ok(())
+}
+
+/// Writes a number as ECMAScript's `Number.prototype.toString` does, as required by
+/// RFC 8785: shortest round-trippable digits, exponent form below `1e-6` and from `1e21`.
+fn write_canonical_number(buffer: &mut String, float: f64) {
+    if float == 0.0 {
+        // Includes -0.0.
+        buffer.push('0');
+        return;
+    }
+    if float < 0.0 {
+        buffer.push('-');
+    }
+    // `{:e}` gives the shortest round-trippable digits, like `1.2345e-7`.
+    let scientific = format!("{:e}", float.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let digit_count = digits.len() as i32;
+    // The position of the decimal point relative to the start of `digits`.
+    let point = exponent.parse::<i32>().unwrap() + 1;
+    if digit_count <= point && point <= 21 {
+        buffer.push_str(&digits);
+        buffer.push_str(&"0".repeat((point - digit_count) as usize));
+    } else if 0 < point && point <= 21 {
+        buffer.push_str(&digits[..point as usize]);
+        buffer.push('.');
+        buffer.push_str(&digits[point as usize..]);
+    } else if -6 < point && point <= 0 {
+        buffer.push_str("0.");
+        buffer.push_str(&"0".repeat(-point as usize));
+        buffer.push_str(&digits);
+    } else {
+        buffer.push_str(&digits[..1]);
+        if digit_count > 1 {
+            buffer.push('.');
+            buffer.push_str(&digits[1..]);
         }
-        Ok(())
-    })?;
-    if !formatter.sameline {
-        write!(writer, "\n")?;
-        write!(writer, "{}", formatter.indentation())?;
+        write!(buffer, "e{:+}", point - 1).unwrap();
     }
-    write!(writer, "}}")
 }
 
-fn write_value<W: Write>(writer: &mut W, value: &Value, formatter: JsonFormatter) -> std::fmt::Result {
+fn write_canonical_value(buffer: &mut String, value: &Value) -> Result<(), CanonicalizeError> {
     match value {
-        Value::Null => write_null(writer),
-        &Value::Boolean(boolean) => write_boolean(writer, boolean),
-        &Value::Number(number) => write_number(writer, number),
-        Value::String(string) => write_string(writer, string),
-        Value::Array(array) => write_array(writer, array, formatter),
-        Value::Object(object) => write_object(writer, object, formatter),
+        Value::Number(number) => {
+            let float = number.as_f64();
+            if !float.is_finite() {
+                return Err(CanonicalizeError::NonFiniteNumber(float));
+            }
+            write_canonical_number(buffer, float);
+        }
+        Value::Array(array) => {
+            buffer.push('[');
+            for (index, value) in array.iter().enumerate() {
+                if index != 0 {
+                    buffer.push(',');
+                }
+                write_canonical_value(buffer, value)?;
+            }
+            buffer.push(']');
+        }
+        Value::Object(object) => {
+            // Keys are sorted by their UTF-16 code units, not by bytes or chars.
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            buffer.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index != 0 {
+                    buffer.push(',');
+                }
+                write_string(buffer, key, JsonFormatter::default()).unwrap();
+                buffer.push(':');
+                write_canonical_value(buffer, value)?;
+            }
+            buffer.push('}');
+        }
+        // Strings use the minimal escapes, exactly as RFC 8785 requires.
+        scalar => write_value(buffer, scalar, JsonFormatter::default()).unwrap(),
     }
+    Ok(())
 }
 
-impl std::fmt::Display for Value {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write_value(f, self, JsonFormatter::new(true, false, Indent::Spaces(0)))
+impl core::fmt::Display for Value {
+    /// Writes compact JSON, as the default [Formatter] does. The alternate flag (`{:#}`)
+    /// writes pretty JSON with the default indentation instead.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Formatter::new().pretty(f.alternate()).write(self, f)
+    }
+}
+
+/// Configurable serializer for [Value]s.
+/// ```
+/// # use bourne::{Value, format::{Formatter, Indent}};
+/// let value = Value::from("a/b");
+/// assert_eq!(Formatter::new().escape_solidus(true).format(&value), r#""a\/b""#);
+/// let value = Value::array().with_item(1i64);
+/// assert_eq!(Formatter::new().pretty(true).indent(Indent::Spaces(2)).format(&value), "[\n  1\n]");
+/// assert_eq!(Formatter::new().pretty(true).indent("\t").format(&value), "[\n\t1\n]");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Formatter {
+    pretty: bool,
+    indent: Indent,
+    escape: EscapeOptions,
+    sort_keys: bool,
+    collapse_after_depth: Option<usize>,
+    non_finite: NonFiniteMode,
+    minimal_escapes: bool,
+}
+
+impl Formatter {
+    /// Create a [Formatter] with the default options (compact output).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write each array element and object entry on its own line, indented by
+    /// [Formatter::indent], with spaces around colons. Default: `false`.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// The [Indent] used per nesting level when [Formatter::pretty] is set, either as an
+    /// [Indent] or a string such as `"  "` or `"\t"`. Default: `Indent::Spaces(4)`.
+    pub fn indent(mut self, indent: impl Into<Indent>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// When [Formatter::pretty] is set, write arrays and objects nested more than `depth`
+    /// levels deep compactly on a single line, keeping the top levels readable. With
+    /// `Some(0)` the whole value is compact. Default: [None].
+    pub fn collapse_after_depth(mut self, depth: Option<usize>) -> Self {
+        self.collapse_after_depth = depth;
+        self
+    }
+
+    /// Escape `/` as `\/`. This is allowed but not required by JSON, and is sometimes
+    /// used to make it safe to embed JSON in HTML `<script>` tags. Default: `false`.
+    pub fn escape_solidus(mut self, escape_solidus: bool) -> Self {
+        self.escape.solidus = escape_solidus;
+        self
+    }
+
+    /// Escape every non-ASCII character as `\uXXXX` (as a surrogate pair for characters
+    /// outside the Basic Multilingual Plane), so the output is pure ASCII. Default: `false`.
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.escape.ascii_only = ascii_only;
+        self
+    }
+
+    /// Only write the escapes JSON requires: `\"`, `\\` and control characters, leaving `/`
+    /// and all other Unicode literal. This overrides [Formatter::escape_solidus] and
+    /// [Formatter::ascii_only], whichever order they are set in. Without those two, the
+    /// output is already minimal. Default: `false`.
+    pub fn minimal_escapes(mut self, minimal_escapes: bool) -> Self {
+        self.minimal_escapes = minimal_escapes;
+        self
+    }
+
+    /// Write object keys in lexicographical (byte-wise) order, regardless of the order
+    /// of the underlying [ValueMap]. Useful for deterministic output. Default: `false`.
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// How to write NaN and the infinities. See [NonFiniteMode]. Default: [NonFiniteMode::Null].
+    pub fn non_finite(mut self, non_finite: NonFiniteMode) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    fn json_formatter(&self) -> JsonFormatter {
+        let mut formatter = if self.pretty {
+            JsonFormatter::new(false, true, self.indent)
+        } else {
+            JsonFormatter::new(true, false, Indent::Spaces(0))
+        };
+        formatter.escape = if self.minimal_escapes { EscapeOptions::default() } else { self.escape };
+        formatter.sort_keys = self.sort_keys;
+        formatter.collapse_after_depth = self.collapse_after_depth;
+        formatter.non_finite = self.non_finite;
+        formatter
+    }
+
+    /// Serialize `value` to a [String].
+    ///
+    /// # Panics
+    /// With [NonFiniteMode::Error], if `value` contains NaN or an infinity. Use
+    /// [Formatter::try_format] to handle that as an error instead.
+    pub fn format(&self, value: &Value) -> String {
+        match self.try_format(value) {
+            Ok(json) => json,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Serialize `value` to a [String], returning [FormatError::NonFiniteNumber] for NaN
+    /// and the infinities with [NonFiniteMode::Error].
+    pub fn try_format(&self, value: &Value) -> Result<String, FormatError> {
+        let mut buffer = String::new();
+        match try_write_value(&mut buffer, value, self.json_formatter()) {
+            Ok(()) => Ok(buffer),
+            Err(WriteError::NonFinite(float)) => Err(FormatError::NonFiniteNumber(float)),
+            // Writing to a String is infallible.
+            Err(WriteError::Fmt(error)) => panic!("{error}"),
+        }
+    }
+
+    /// Serialize `value` to `writer`.
+    pub fn write<W: Write>(&self, value: &Value, writer: &mut W) -> core::fmt::Result {
+        write_value(writer, value, self.json_formatter())
     }
 }
 
 pub struct PrettyPrint<'a>(&'a Value, Indent, bool);
 
-impl<'a> std::fmt::Display for PrettyPrint<'a> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Display for PrettyPrint<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write_value(f, self.0, JsonFormatter::new(false, self.2, self.1))
     }
 }
@@ -295,4 +658,348 @@ impl Value {
     pub fn pretty_print(&self) -> PrettyPrint<'_> {
         PrettyPrint(self, Indent::Spaces(4), true)
     }
-}
\ No newline at end of file
+
+    /// Convert any [Value] to a [String]. Unlike [Value::as_str], which only succeeds for
+    /// [Value::String], this returns strings without quotes or escapes, and the JSON text
+    /// for everything else (`null`, `true`, `1.5`, `[1,2]`).
+    pub fn coerce_to_string(&self) -> String {
+        match self {
+            Value::String(string) => string.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Serialize to canonical JSON as defined by the JSON Canonicalization Scheme
+    /// (RFC 8785): object keys sorted by their UTF-16 code units, no whitespace, minimal
+    /// string escapes, and numbers formatted like ECMAScript does. Every [Number] is
+    /// treated as an [f64], so integers beyond 2^53 may lose precision.
+    ///
+    /// Returns [CanonicalizeError::NonFiniteNumber] for NaN and the infinities.
+    pub fn to_canonical_string(&self) -> Result<String, CanonicalizeError> {
+        let mut buffer = String::new();
+        write_canonical_value(&mut buffer, self)?;
+        Ok(buffer)
+    }
+
+    /// Pretty print to `writer` with the default [Indent]. Arrays and objects nested more
+    /// than `collapse_after_depth` levels deep are written compactly on a single line.
+    /// See [Formatter::collapse_after_depth].
+    pub fn pretty_to_writer<W: Write>(&self, writer: &mut W, collapse_after_depth: Option<usize>) -> core::fmt::Result {
+        Formatter::new().pretty(true).collapse_after_depth(collapse_after_depth).write(self, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+    use super::*;
+
+    #[test]
+    fn escape_test() {
+        let value = Value::from("nul:\u{0}, tab:\t, newline:\n, unit:\u{1f}, quote:\", backslash:\\, \u{8}\u{c}\r, a/b");
+        assert_eq!(
+            value.to_string(),
+            r#""nul:\u0000, tab:\t, newline:\n, unit:\u001f, quote:\", backslash:\\, \b\f\r, a/b""#,
+        );
+        assert_eq!(
+            Formatter::new().escape_solidus(true).format(&value),
+            r#""nul:\u0000, tab:\t, newline:\n, unit:\u001f, quote:\", backslash:\\, \b\f\r, a\/b""#,
+        );
+        assert_eq!(escape_string("\u{0}\t\n"), r#"\u0000\t\n"#);
+        assert_eq!(measure_escaped_string("\u{0}\t\n"), escape_string("\u{0}\t\n").len());
+    }
+
+    #[test]
+    fn escape_round_trip_test() -> Result<(), crate::error::ParseError> {
+        use crate::parse::unescape_string;
+
+        let strings = [
+            String::new(),
+            "plain".to_owned(),
+            (0..0x20).map(char::from).collect(),
+            "\"quoted\" \\ back\\slash / solidus \\/ \\u0041".to_owned(),
+            "caf\u{e9} \u{7f}\u{80} \u{ffff} \u{10000} \u{1f600} \u{10ffff}".to_owned(),
+            "\u{2028}\u{2029}\u{feff}".to_owned(),
+        ];
+        let all_options = [(false, false), (true, false), (false, true), (true, true)]
+            .map(|(solidus, ascii_only)| EscapeOptions { solidus, ascii_only });
+        for string in &strings {
+            for options in all_options {
+                let escaped = escape_string_with_options(string, options);
+                assert_eq!(&unescape_string(&escaped)?, string, "{options:?}");
+                assert_eq!(Value::from_str(&format!("\"{escaped}\""))?, *string.as_str());
+                if options.ascii_only {
+                    assert!(escaped.is_ascii());
+                }
+            }
+        }
+        assert_eq!(escape_string_with_options("\u{1f600}/", EscapeOptions { solidus: true, ascii_only: true }), r"\ud83d\ude00\/");
+        Ok(())
+    }
+
+    #[test]
+    fn ascii_only_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        let value = Value::from("caf\u{e9} \u{1f600}");
+        let ascii = Formatter::new().ascii_only(true).format(&value);
+        assert_eq!(ascii, r#""caf\u00e9 \ud83d\ude00""#);
+        assert!(ascii.is_ascii());
+        assert_eq!(Value::from_str(&ascii)?, value);
+        assert_eq!(value.to_string(), "\"caf\u{e9} \u{1f600}\"");
+        Ok(())
+    }
+
+    #[test]
+    fn float_round_trip_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        for (text, expected) in [
+            ("2.0", "2.0"),
+            ("1e10", "10000000000.0"),
+            ("-0.0", "-0.0"),
+            ("1e100", "1e100"),
+            ("[2.0, 2]", "[2.0,2]"),
+        ] {
+            let value = Value::from_str(text)?;
+            let json = value.to_string();
+            #[cfg(not(feature = "arbitrary_precision"))]
+            assert_eq!(json, expected);
+            // Raw numbers are written as they were parsed.
+            #[cfg(feature = "arbitrary_precision")]
+            assert_eq!(json, text.replace(' ', ""), "{expected}");
+            let reparsed = Value::from_str(&json)?;
+            assert_eq!(format!("{reparsed:?}"), format!("{value:?}"));
+        }
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            let Value::Number(Number::Float(zero)) = Value::from_str("-0.0")? else {
+                panic!("Not a float.");
+            };
+            assert!(zero.is_sign_negative());
+        }
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            let zero = Value::from_str("-0.0")?;
+            assert!(zero.strict_eq(&Value::from(0.0)));
+            assert!(zero.as_f64().is_some_and(f64::is_sign_negative));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn sort_keys_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        let value = Value::from_str(r#"{"zeta": 1, "alpha": {"y": 2, "x": 3}, "mid": [{"b": 1, "a": 2}], "Beta": null}"#)?;
+        let formatter = Formatter::new().sort_keys(true);
+        let json = formatter.format(&value);
+        assert_eq!(json, r#"{"Beta":null,"alpha":{"x":3,"y":2},"mid":[{"a":2,"b":1}],"zeta":1}"#);
+        assert_eq!(formatter.format(&value.clone()), json);
+        Ok(())
+    }
+
+    #[test]
+    fn formatter_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        let value = Value::from_str(r#"{"b": ["x/y", "é"], "a": 1}"#)?;
+        let formatter = Formatter::new().sort_keys(true);
+        assert_eq!(formatter.format(&value), r#"{"a":1,"b":["x/y","é"]}"#);
+        assert_eq!(
+            formatter.escape_solidus(true).ascii_only(true).format(&value),
+            r#"{"a":1,"b":["x\/y","\u00e9"]}"#,
+        );
+        assert_eq!(
+            formatter.pretty(true).indent(Indent::Spaces(2)).format(&value),
+            "{\n  \"a\" : 1,\n  \"b\" : [\n    \"x/y\",\n    \"é\"\n  ]\n}",
+        );
+        assert_eq!(
+            formatter.pretty(true).indent(Indent::Tabs(1)).format(&value["b"]),
+            "[\n\t\"x/y\",\n\t\"é\"\n]",
+        );
+        assert_eq!(
+            formatter.pretty(true).indent("\t").format(&value["b"]),
+            formatter.pretty(true).indent(Indent::Tabs(1)).format(&value["b"]),
+        );
+        assert_eq!(Indent::from("  "), Indent::Spaces(2));
+        assert_eq!(Indent::from("\t\t"), Indent::Tabs(2));
+        assert_eq!(Indent::from(""), Indent::Spaces(0));
+        assert_eq!(
+            Formatter::new().pretty(true).format(&value),
+            value.pretty_print().to_string(),
+        );
+        let mut buffer = String::from("json: ");
+        formatter.write(&value, &mut buffer).unwrap();
+        assert_eq!(buffer, r#"json: {"a":1,"b":["x/y","é"]}"#);
+        assert_eq!(Formatter::new().format(&value), value.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn minimal_escapes_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        let value = Value::from("Party 🎉\n\"now\"\t\u{1}/é");
+        let minimal = Formatter::new().minimal_escapes(true);
+        let json = minimal.format(&value);
+        assert_eq!(json, r#""Party 🎉\n\"now\"\t\u0001/é""#);
+        assert_eq!(Value::from_str(&json)?, value);
+        assert_eq!(value.to_string(), json);
+        let strict = Formatter::new().ascii_only(true).escape_solidus(true);
+        assert_eq!(strict.format(&value), r#""Party \ud83c\udf89\n\"now\"\t\u0001\/\u00e9""#);
+        assert_eq!(strict.minimal_escapes(true).format(&value), json);
+        assert_eq!(minimal.ascii_only(true).format(&value), json);
+        Ok(())
+    }
+
+    #[test]
+    fn non_finite_test() {
+        let value = Value::from(vec![f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.5]);
+        assert_eq!(value.to_string(), "[null,null,null,1.5]");
+        assert_eq!(Formatter::new().non_finite(NonFiniteMode::Null).format(&value), "[null,null,null,1.5]");
+        assert_eq!(
+            Formatter::new().non_finite(NonFiniteMode::String).format(&value),
+            r#"["NaN","Infinity","-Infinity",1.5]"#,
+        );
+        let strict = Formatter::new().non_finite(NonFiniteMode::Error);
+        assert!(matches!(strict.try_format(&value), Err(FormatError::NonFiniteNumber(nan)) if nan.is_nan()));
+        assert_eq!(
+            strict.try_format(&Value::from(f64::INFINITY)),
+            Err(FormatError::NonFiniteNumber(f64::INFINITY)),
+        );
+        assert_eq!(strict.write(&value, &mut String::new()), Err(core::fmt::Error));
+        assert_eq!(strict.format(&Value::from(1.5)), "1.5");
+        assert_eq!(strict.try_format(&Value::from(1.5)), Ok("1.5".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Non-finite number inf")]
+    fn non_finite_error_format_test() {
+        Formatter::new().non_finite(NonFiniteMode::Error).format(&Value::from(vec![1.0, f64::INFINITY]));
+    }
+
+    #[test]
+    fn display_alternate_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        let value = Value::from_str(r#"{"a": [1, {"b": null}]}"#)?;
+        assert_eq!(format!("{value}"), r#"{"a":[1,{"b":null}]}"#);
+        assert_eq!(
+            format!("{value:#}"),
+            "{\n    \"a\" : [\n        1,\n        {\n            \"b\" : null\n        }\n    ]\n}",
+        );
+        assert_eq!(format!("{value:#}"), value.pretty_print().to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn collapse_after_depth_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        let value = Value::from_str(r#"{"a": {"b": {"c": [1, 2]}}}"#)?;
+        let pretty = |depth| {
+            let mut buffer = String::new();
+            value.pretty_to_writer(&mut buffer, depth).unwrap();
+            buffer
+        };
+        assert_eq!(pretty(Some(1)), "{\n    \"a\" : {\"b\":{\"c\":[1,2]}}\n}");
+        assert_eq!(pretty(Some(2)), "{\n    \"a\" : {\n        \"b\" : {\"c\":[1,2]}\n    }\n}");
+        assert_eq!(pretty(Some(0)), value.to_string());
+        assert_eq!(pretty(None), value.pretty_print().to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        // Test vectors from RFC 8785, sections 3.2.2 and 3.2.3.
+        let value = Value::from_str(r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#)?;
+        assert_eq!(
+            value.to_canonical_string(),
+            Ok(r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#.to_string()),
+        );
+        let value = Value::from_str(r#"{
+            "\u20ac": "Euro Sign",
+            "\r": "Carriage Return",
+            "\ufb33": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\ud83d\ude00": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "\u00f6": "Latin Small Letter O With Diaeresis"
+        }"#)?;
+        let keys = Value::from_str(&value.to_canonical_string().unwrap())?;
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\
+            \"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\
+            \"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}",
+        );
+        assert_eq!(keys, value);
+
+        // Number vectors from Appendix B.
+        for (number, expected) in [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (5e-324, "5e-324"),
+            (-1.7976931348623157e308, "-1.7976931348623157e+308"),
+            (9007199254740992.0, "9007199254740992"),
+            (-9007199254740992.0, "-9007199254740992"),
+            (295147905179352830000.0, "295147905179352830000"),
+            (1e21, "1e+21"),
+            (1e20, "100000000000000000000"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (1.5, "1.5"),
+        ] {
+            assert_eq!(Value::from(number).to_canonical_string().as_deref(), Ok(expected));
+        }
+        assert_eq!(Value::from(7i64).to_canonical_string().as_deref(), Ok("7"));
+        assert_eq!(
+            Value::from(f64::NAN).to_canonical_string().map_err(|err| err.to_string()),
+            Err("Non-finite number NaN cannot be represented in canonical JSON.".to_string()),
+        );
+        assert!(matches!(
+            Value::from(f64::INFINITY).to_canonical_string(),
+            Err(CanonicalizeError::NonFiniteNumber(float)) if float == f64::INFINITY,
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn coerce_to_string_test() -> Result<(), crate::error::ParseError> {
+        use std::str::FromStr;
+
+        assert_eq!(Value::from("a \"quoted\" string").coerce_to_string(), "a \"quoted\" string");
+        assert_eq!(Value::from(42i64).coerce_to_string(), "42");
+        assert_eq!(Value::from(2.0).coerce_to_string(), "2.0");
+        assert_eq!(Value::from(true).coerce_to_string(), "true");
+        assert_eq!(Value::from(false).coerce_to_string(), "false");
+        assert_eq!(Value::Null.coerce_to_string(), "null");
+        assert_eq!(Value::from_str(r#"[1, "two"]"#)?.coerce_to_string(), r#"[1,"two"]"#);
+        assert_eq!(Value::from_str(r#"{"a": "b"}"#)?.coerce_to_string(), r#"{"a":"b"}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn deep_nesting_test() {
+        const DEPTH: usize = 100_000;
+        let mut value = Value::Null;
+        for _ in 0..DEPTH {
+            value = Value::Array(vec![value]);
+        }
+        let json = value.to_string();
+        assert!(json == "[".repeat(DEPTH) + "null" + &"]".repeat(DEPTH));
+        let pretty = Formatter::new().pretty(true).collapse_after_depth(Some(1)).format(&value);
+        assert_eq!(pretty.lines().count(), 3);
+        // Dropping the value recursively would overflow the stack, so take it apart first.
+        while let Value::Array(mut array) = value {
+            value = array.pop().unwrap_or_default();
+        }
+    }
+}
@@ -1,63 +1,248 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+extern crate alloc;
+
 pub mod error;
 pub mod parse;
 pub mod format;
+pub mod diff;
+pub mod patch;
+#[cfg(feature = "base64")]
+mod binary;
+mod cmp;
+mod convert;
+mod env;
+mod flatten;
+mod pointer;
+mod query;
+mod query_string;
+mod shape;
+#[cfg(feature = "std")]
+mod stream;
+/// Create a [Value] from JSON syntax. Expressions are allowed as values as long as the
+/// result is convertible to a [Value].
+/// ```
+/// use bourne::json;
+/// let number = 3i64;
+/// let value = json!(
+///     {
+///         "number" : number
+///     }
+/// );
+/// assert_eq!(value.to_string(), r#"{"number":3}"#);
+/// ```
 pub use bournemacro::json;
+pub use convert::{FromValue, ToValue};
+pub use pointer::PathSegment;
+pub use shape::Shape;
+
+use error::ConversionError;
+#[allow(unused_imports)]
+use prelude::*;
+
+/// The parts of the standard prelude that come from `alloc`, for builds without `std`.
+mod prelude {
+    #[allow(unused_imports)]
+    pub(crate) use alloc::{borrow::ToOwned, boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+}
 
 /// The Mapping that [Value] uses for [Value::Object].  
 /// Uses [hashbrown::HashMap].
 #[cfg(not(feature = "preserve_order"))]
-pub type ValueMap = hashbrown::HashMap<String, Value>;
+pub type ValueMap = hashbrown::HashMap<Key, Value>;
 /// The Mapping that [Value] uses for [Value::Object].  
 /// Uses [indexmap::IndexMap] (`preserve_order` feature is on)
 #[cfg(feature = "preserve_order")]
-pub type ValueMap = indexmap::IndexMap<String, Value>;
+pub type ValueMap = indexmap::IndexMap<Key, Value>;
+
+/// The key type of [ValueMap]. A [String].
+#[cfg(not(feature = "shared_keys"))]
+pub type Key = String;
+/// The key type of [ValueMap]. An [Arc](alloc::sync::Arc)`<str>` (`shared_keys` feature
+/// is on), so that the parser can give every occurrence of a repeated key the same storage.
+#[cfg(feature = "shared_keys")]
+pub type Key = alloc::sync::Arc<str>;
+
+/// Convert an owned [String] into a [Key], which is free without `shared_keys`.
+#[allow(clippy::useless_conversion)]
+pub(crate) fn into_key(key: String) -> Key {
+    key.into()
+}
+
+/// An entry in a [Value::Object], returned by [Value::entry]. The same type is used with
+/// and without the `preserve_order` feature.
+#[derive(Debug)]
+pub struct Entry<'a> {
+    #[cfg(not(feature = "preserve_order"))]
+    inner: hashbrown::hash_map::Entry<'a, Key, Value, hashbrown::hash_map::DefaultHashBuilder>,
+    #[cfg(feature = "preserve_order")]
+    inner: indexmap::map::Entry<'a, Key, Value>,
+}
+
+impl<'a> Entry<'a> {
+    /// Get the value of the entry, inserting `default` if the key is absent.
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        self.inner.or_insert(default)
+    }
+
+    /// Get the value of the entry, inserting the result of `default` if the key is absent.
+    pub fn or_insert_with<F: FnOnce() -> Value>(self, default: F) -> &'a mut Value {
+        self.inner.or_insert_with(default)
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A JSON number.
+///
+/// Numbers compare by value, regardless of variant. `NaN` is considered equal
+/// to itself so that [Number] and [Value] can implement [Eq] and [Hash]. A
+/// [Number::Raw] compares exactly, without rounding through an [f64], against both
+/// other raw numbers and the exact value of a [Number::Float].
+#[derive(Debug, Clone)]
 pub enum Number {
     Float(f64),
     Int(i64),
+    /// Used for integers above [i64::MAX].
+    Uint(u64),
+    /// A number kept as its original text, produced when
+    /// [ParseOptions::preserve_number_text](parse::ParseOptions::preserve_number_text) is set
+    /// or the `arbitrary_precision` feature is enabled.
+    /// It is written back verbatim, and otherwise behaves like the number it represents.
+    /// The text must be a valid JSON number; text that doesn't parse as one behaves as NaN.
+    Raw(String),
+}
+
+/// A [Number] with [Number::Raw] text parsed into one of the other variants.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Resolved {
+    Float(f64),
+    Int(i64),
+    Uint(u64),
+}
+
+impl Resolved {
+    /// The value as an [f64]. Integers outside of ±2^53 lose precision.
+    pub(crate) fn as_f64(self) -> f64 {
+        match self {
+            Resolved::Float(float) => float,
+            Resolved::Int(int) => int as f64,
+            Resolved::Uint(uint) => uint as f64,
+        }
+    }
+
+    /// The value of an integer variant, widened so that every integer variant fits.
+    pub(crate) fn integer(self) -> Option<i128> {
+        match self {
+            Resolved::Int(int) => Some(int as i128),
+            Resolved::Uint(uint) => Some(uint as i128),
+            Resolved::Float(_) => None,
+        }
+    }
+}
+
+impl Number {
+    /// The [Number::Int], or [Number::Uint] if it only fits in a [u64], that `float` is
+    /// exactly equal to. Returns [None] for floats with a fractional part, floats outside
+    /// the [u64] and [i64] ranges, and `-0.0`.
+    pub(crate) fn integral(float: f64) -> Option<Number> {
+        let int = cmp::integral_value_i128(float)?;
+        if float.is_sign_negative() && int == 0 {
+            return None;
+        }
+        if let Ok(int) = i64::try_from(int) {
+            Some(Number::Int(int))
+        } else {
+            u64::try_from(int).ok().map(Number::Uint)
+        }
+    }
+
+    /// The number with [Number::Raw] text parsed as the parser would without keeping the
+    /// text: `-0` is `-0.0`, and text that isn't a number is NaN.
+    pub(crate) fn resolve(&self) -> Resolved {
+        let text = match *self {
+            Number::Float(float) => return Resolved::Float(float),
+            Number::Int(int) => return Resolved::Int(int),
+            Number::Uint(uint) => return Resolved::Uint(uint),
+            Number::Raw(ref text) => text,
+        };
+        if !text.contains(['.', 'e', 'E']) {
+            match text.parse() {
+                // Keep the sign of `-0`, which an integer can't represent.
+                Ok(0) if text.starts_with('-') => return Resolved::Float(-0.0),
+                Ok(int) => return Resolved::Int(int),
+                Err(_) => (),
+            }
+            if let Ok(uint) = text.parse() {
+                return Resolved::Uint(uint);
+            }
+        }
+        Resolved::Float(text.parse().unwrap_or(f64::NAN))
+    }
+
+    /// Get the number as an [f64]. Integers outside of ±2^53 lose precision.
+    pub fn as_f64(&self) -> f64 {
+        self.resolve().as_f64()
+    }
+
+    /// Get the number as an [i64]. Returns [None] for floats that aren't integral
+    /// or that are out of range.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.resolve() {
+            Resolved::Float(float) => cmp::integral_value(float),
+            Resolved::Int(int) => Some(int),
+            Resolved::Uint(uint) => i64::try_from(uint).ok(),
+        }
+    }
+
+    /// Get the number as a [u64]. Returns [None] for negative numbers, and for floats
+    /// that aren't integral or that are out of range.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.resolve() {
+            Resolved::Float(float) => cmp::integral_value_i128(float).and_then(|int| u64::try_from(int).ok()),
+            Resolved::Int(int) => u64::try_from(int).ok(),
+            Resolved::Uint(uint) => Some(uint),
+        }
+    }
 }
 
 /// JSON Value.
-#[derive(Debug, Clone)]
+/// 
+/// Objects compare and hash independently of key order, and numbers compare by
+/// value (see [Number]), so [Value]s can be used in a [std::collections::HashSet].
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     /// Null value.
-    /// ```no_run,json
+    /// ```json
     /// null
     /// ```
     Null,
     /// A boolean value.
-    /// ```no_run,json
+    /// ```json
     /// true
     /// ```
     /// or
-    /// ```no_run,json
+    /// ```json
     /// false
     /// ```
     Boolean(bool),
-    /// An [f64] or [i64] number.
-    /// ```no_run,json
+    /// An [f64], [i64] or [u64] number.
+    /// ```json
     /// 3.14159265358979
     /// ```
     Number(Number),
     /// A UTF-8 encoded string.
-    /// ```no_run,json
+    /// ```json
     /// "The quick brown fox jumps over the lazy dog.\nhello, world"
     /// ```
-    /// The following characters must be escaped:  
-    /// * `\u{0}`to `\u{1f}` (inclusive)
-    /// * `\n` (newline)
-    /// * `\r` (carriage return)
-    /// * `\t` (tab) (optional)
-    /// * `"`
-    /// * `'` (optional)
-    /// * `\`
-    /// * `/` (optional)
-    /// * `\u{8}`
-    /// * `\u{c}`
+    /// The following characters are always escaped when serializing:  
+    /// * `"` as `\"`
+    /// * `\` as `\\`
+    /// * `\n` (newline), `\r` (carriage return), `\t` (tab), `\u{8}` and `\u{c}`
+    ///   as `\n`, `\r`, `\t`, `\b` and `\f`
+    /// * Any other character from `\u{0}` to `\u{1f}` (inclusive) as `\u00XX`
+    /// 
+    /// `/` is escaped as `\/` when [format::Formatter::escape_solidus] is enabled.
     String(String),
     /// An array of JSON [Value]s.
-    /// ```no_run,json
+    /// ```json
     /// [
     ///     "hello, world",
     ///     1234,
@@ -69,7 +254,7 @@ pub enum Value {
     /// ```
     Array(Vec<Value>),
     /// A Mapping of JSON [Value]s by their name.
-    /// ```no_run,json
+    /// ```json
     /// {
     ///     "tag": null,
     ///     "registered": true,
@@ -91,6 +276,20 @@ pub enum Value {
     Object(ValueMap),
 }
 
+impl Default for Number {
+    /// Number::Int(0)
+    fn default() -> Self {
+        Number::Int(0)
+    }
+}
+
+impl Default for Value {
+    /// Value::Null
+    fn default() -> Self {
+        Value::Null
+    }
+}
+
 impl From<bool> for Value {
     /// Create a [Value] from a [bool].
     fn from(value: bool) -> Self {
@@ -119,10 +318,24 @@ impl From<&str> for Value {
     }
 }
 
-impl From<Vec<Value>> for Value {
-    /// Create a [Value] from a [Vec<Value>]
-    fn from(value: Vec<Value>) -> Self {
-        Value::Array(value)
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    /// Create a [Value::Array] from a [Vec], converting each element.
+    fn from(value: Vec<T>) -> Self {
+        Value::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>, const N: usize> From<[T; N]> for Value {
+    /// Create a [Value::Array] from an array, converting each element.
+    fn from(value: [T; N]) -> Self {
+        Value::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value> + Clone> From<&[T]> for Value {
+    /// Create a [Value::Array] from a slice, converting a clone of each element.
+    fn from(value: &[T]) -> Self {
+        Value::Array(value.iter().cloned().map(Into::into).collect())
     }
 }
 
@@ -133,6 +346,22 @@ impl From<ValueMap> for Value {
     }
 }
 
+#[cfg(feature = "std")]
+impl<T: Into<Value>> From<std::collections::HashMap<String, T>> for Value {
+    /// Create a [Value::Object] from a [HashMap](std::collections::HashMap), converting each value.
+    fn from(value: std::collections::HashMap<String, T>) -> Self {
+        Value::Object(value.into_iter().map(|(key, value)| (into_key(key), value.into())).collect())
+    }
+}
+
+impl<T: Into<Value>> From<alloc::collections::BTreeMap<String, T>> for Value {
+    /// Create a [Value::Object] from a [BTreeMap](std::collections::BTreeMap), converting
+    /// each value. With `preserve_order`, the keys stay sorted.
+    fn from(value: alloc::collections::BTreeMap<String, T>) -> Self {
+        Value::Object(value.into_iter().map(|(key, value)| (into_key(key), value.into())).collect())
+    }
+}
+
 impl From<i64> for Value {
     /// Create a [Value] from an [i64]. Note that there is loss in precision because this value will be converted to [f64].
     fn from(value: i64) -> Self {
@@ -140,6 +369,76 @@ impl From<i64> for Value {
     }
 }
 
+impl From<u64> for Value {
+    /// Create a [Value] from a [u64]. Values that fit in an [i64] become [Number::Int],
+    /// larger values become [Number::Uint].
+    fn from(value: u64) -> Self {
+        match i64::try_from(value) {
+            Ok(int) => Value::Number(Number::Int(int)),
+            Err(_) => Value::Number(Number::Uint(value)),
+        }
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    /// Create a [Value] from an [Option]. [None] becomes [Value::Null].
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl TryFrom<&Value> for i64 {
+    type Error = ConversionError;
+    /// Convert a [Value::Number] to an [i64]. Integral floats are allowed.
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_i64().ok_or(ConversionError { expected: "integer", found: value.type_name() })
+    }
+}
+
+impl TryFrom<&Value> for f64 {
+    type Error = ConversionError;
+    /// Convert a [Value::Number] to an [f64].
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or(ConversionError { expected: "number", found: value.type_name() })
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = ConversionError;
+    /// Convert a [Value::Boolean] to a [bool].
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or(ConversionError { expected: "boolean", found: value.type_name() })
+    }
+}
+
+impl TryFrom<&Value> for String {
+    type Error = ConversionError;
+    /// Convert a [Value::String] to a [String].
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_str().map(str::to_owned).ok_or(ConversionError { expected: "string", found: value.type_name() })
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for &'a str {
+    type Error = ConversionError;
+    /// Borrow the contents of a [Value::String].
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        value.as_str().ok_or(ConversionError { expected: "string", found: value.type_name() })
+    }
+}
+
+/// Whether an object key is missing, explicitly null, or has a value. Returned by
+/// [Value::get_field_status].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldStatus<'a> {
+    /// The key is not present, or the value is not a [Value::Object].
+    Absent,
+    /// The key is present with the value [Value::Null].
+    Null,
+    /// The key is present with any other value.
+    Present(&'a Value),
+}
+
 /// Allows for indexing into a [Value] by [String] or [usize]
 pub trait IndexOrKey {
     /// Get an immutable reference to a [Value].
@@ -203,7 +502,7 @@ impl IndexOrKey for &str {
         let Value::Object(object) = value else {
             panic!("Not an object.");
         };
-        object.entry(self.to_owned()).or_insert(Value::Null)
+        object.entry(Key::from(self)).or_insert(Value::Null)
     }
 }
 
@@ -213,7 +512,7 @@ impl IndexOrKey for String {
         let Value::Object(object) = value else {
             return None;
         };
-        object.get(&self)
+        object.get(self.as_str())
     }
 
     /// Get a mutable reference to a [Value] in a [Value::Object].
@@ -221,7 +520,7 @@ impl IndexOrKey for String {
         let Value::Object(object) = value else {
             return None;
         };
-        object.get_mut(&self)
+        object.get_mut(self.as_str())
     }
 
     /// Get a mutable reference to a [Value] in a [Value::Object] if it exists, otherwise
@@ -233,7 +532,7 @@ impl IndexOrKey for String {
         let Value::Object(object) = value else {
             panic!("Not an object.");
         };
-        object.entry(self).or_insert(Value::Null)
+        object.entry(into_key(self)).or_insert(Value::Null)
     }
 }
 
@@ -244,29 +543,203 @@ pub trait InsertKey {
 
 impl InsertKey for String {
     fn insert_into(self, map: &mut ValueMap, value: Value) -> Option<Value> {
-        map.insert(self, value)
+        map.insert(into_key(self), value)
     }
 }
 
 impl InsertKey for &str {
     fn insert_into(self, map: &mut ValueMap, value: Value) -> Option<Value> {
-        map.insert(self.to_owned(), value)
+        map.insert(Key::from(self), value)
     }
 }
 
 impl Value {
+    /// Create an empty [Value::Object].
+    pub fn object() -> Self {
+        Value::Object(ValueMap::new())
+    }
+
+    /// Create an empty [Value::Array].
+    pub fn array() -> Self {
+        Value::Array(Vec::new())
+    }
+
+    /// Builder-style [Value::insert]. Returns self with `v` inserted at `k`.
+    /// ```
+    /// # use bourne::Value;
+    /// let value = Value::object()
+    ///     .with("a", 1i64)
+    ///     .with("b", Value::array().with_item(true));
+    /// ```
+    /// 
+    /// Panics if self [Value] is not [Value::Null] or [Value::Object].
+    pub fn with<T: Into<Value>, K: InsertKey>(mut self, k: K, v: T) -> Self {
+        self.insert(k, v);
+        self
+    }
+
+    /// Builder-style [Value::push]. Returns self with `value` pushed onto the end.
+    /// 
+    /// Panics if self [Value] is not [Value::Null] or [Value::Array].
+    pub fn with_item<T: Into<Value>>(mut self, value: T) -> Self {
+        self.push(value);
+        self
+    }
+
+    /// A stable name for this [Value]'s type: `"null"`, `"boolean"`, `"number"`,
+    /// `"string"`, `"array"` or `"object"`. This is also the `found` field of [ConversionError].
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    /// Get the [bool] if this is a [Value::Boolean].
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Boolean(boolean) => Some(boolean),
+            _ => None,
+        }
+    }
+
+    /// Get the number as an [i64] if this is a [Value::Number] holding an integer
+    /// or an integral float (see [Number::as_i64]).
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Number(number) => number.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// Get the [Number] if this is a [Value::Number].
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Value::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+
+    /// Get the number as a [u64] if this is a [Value::Number] holding a non-negative
+    /// integer or integral float (see [Number::as_u64]).
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    /// Get the number as a [usize] if this is a [Value::Number] holding a non-negative
+    /// integer or integral float that fits in a [usize].
+    pub fn as_usize(&self) -> Option<usize> {
+        usize::try_from(self.as_u64()?).ok()
+    }
+
+    /// Get the number as an [i32] if this is a [Value::Number] holding an integer or
+    /// integral float that fits in an [i32].
+    pub fn as_i32(&self) -> Option<i32> {
+        i32::try_from(self.as_i64()?).ok()
+    }
+
+    /// Get the number as an [f64] if this is a [Value::Number].
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(number.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// Parse a [Value::String] holding a JSON number literal, ignoring surrounding whitespace.
+    fn parse_number_string(&self) -> Option<Number> {
+        self.as_str()?.trim().parse().ok()
+    }
+
+    /// Like [Value::as_bool], but also accepts the strings `"true"` and `"false"`,
+    /// ignoring surrounding whitespace.
+    pub fn as_bool_lenient(&self) -> Option<bool> {
+        match self {
+            Value::String(string) => match string.trim() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            },
+            other => other.as_bool(),
+        }
+    }
+
+    /// Like [Value::as_i64], but also accepts a [Value::String] holding a JSON number
+    /// such as `"42"` or `"1e3"`, ignoring surrounding whitespace.
+    pub fn as_i64_lenient(&self) -> Option<i64> {
+        match self {
+            Value::String(_) => self.parse_number_string()?.as_i64(),
+            other => other.as_i64(),
+        }
+    }
+
+    /// Like [Value::as_f64], but also accepts a [Value::String] holding a JSON number
+    /// such as `"1.5"`, ignoring surrounding whitespace.
+    pub fn as_f64_lenient(&self) -> Option<f64> {
+        match self {
+            Value::String(_) => Some(self.parse_number_string()?.as_f64()),
+            other => other.as_f64(),
+        }
+    }
+
+    /// Get the string slice if this is a [Value::String].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Get the array if this is a [Value::Array].
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Get the map if this is a [Value::Object].
+    pub fn as_object(&self) -> Option<&ValueMap> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Take the array out of a [Value::Array], or hand back self unchanged.
+    pub fn into_array(self) -> Result<Vec<Value>, Value> {
+        match self {
+            Value::Array(array) => Ok(array),
+            other => Err(other),
+        }
+    }
+
+    /// Take the map out of a [Value::Object], or hand back self unchanged.
+    pub fn into_object(self) -> Result<ValueMap, Value> {
+        match self {
+            Value::Object(object) => Ok(object),
+            other => Err(other),
+        }
+    }
+
+    /// Take the string out of a [Value::String], or hand back self unchanged.
+    pub fn into_string(self) -> Result<String, Value> {
+        match self {
+            Value::String(string) => Ok(string),
+            other => Err(other),
+        }
+    }
+
     /// Push `value` into a [Value::Array]. If the [Value] is [Value::Null], convert it
     /// into a [Value::Array] and push `value` into it.
     /// 
     /// Panics if self [Value] is not [Value::Null] or [Value::Array].
     pub fn push<T: Into<Value>>(&mut self, value: T) {
-        if let Value::Null = self {
-            *self = Value::Array(Vec::new());
-        }
-        let Value::Array(array) = self else {
-            panic!("Not an array.");
-        };
-        array.push(value.into());
+        self.as_array_mut_or_insert().push(value.into());
     }
 
     /// Insert `value` into a [Value::Object]. If the [Value] is [Value::Null], convert it
@@ -274,62 +747,561 @@ impl Value {
     /// 
     /// Panics if self [Value] is not [Value::Null] or [Value::Array].
     pub fn insert<T: Into<Value>, K: InsertKey>(&mut self, k: K, v: T) -> Option<Value> {
+        k.insert_into(self.as_object_mut_or_insert(), v.into())
+    }
+
+    /// Get the [Entry] for `key` in a [Value::Object] for in-place manipulation. If the
+    /// [Value] is [Value::Null], convert it into an empty [Value::Object] first.
+    /// ```
+    /// # use bourne::Value;
+    /// let mut value = Value::Null;
+    /// value.entry("list").or_insert_with(Value::array).push(1i64);
+    /// ```
+    /// 
+    /// Panics if self [Value] is not [Value::Null] or [Value::Object].
+    pub fn entry<K: Into<Key>>(&mut self, key: K) -> Entry<'_> {
+        Entry { inner: self.as_object_mut_or_insert().entry(key.into()) }
+    }
+
+    /// Get the map of a [Value::Object] for modification. If the [Value] is [Value::Null],
+    /// convert it into an empty [Value::Object] first.
+    ///
+    /// Panics if self [Value] is not [Value::Null] or [Value::Object].
+    pub fn as_object_mut_or_insert(&mut self) -> &mut ValueMap {
         if let Value::Null = self {
             *self = Value::Object(ValueMap::new());
         }
+        match self {
+            Value::Object(object) => object,
+            other => panic!("Expected object or null, found {}.", other.type_name()),
+        }
+    }
+
+    /// Get the elements of a [Value::Array] for modification. If the [Value] is
+    /// [Value::Null], convert it into an empty [Value::Array] first.
+    ///
+    /// Panics if self [Value] is not [Value::Null] or [Value::Array].
+    pub fn as_array_mut_or_insert(&mut self) -> &mut Vec<Value> {
+        if let Value::Null = self {
+            *self = Value::Array(Vec::new());
+        }
+        match self {
+            Value::Array(array) => array,
+            other => panic!("Expected array or null, found {}.", other.type_name()),
+        }
+    }
+
+    /// Get a mutable reference to the [Value] at a dotted `path` such as `"a.b.0"`,
+    /// creating it and any intermediate containers as needed.
+    /// 
+    /// Segments that are decimal numbers index into arrays; other segments are object
+    /// keys. An index at or past the end of the array appends one [Value::Null] and
+    /// refers to that, so arrays are never padded: `"list.7"` on an empty array is
+    /// `list[0]`, and a huge index can't cause a huge allocation. A [Value::Null]
+    /// on the way is turned into the container the next segment needs, and an existing
+    /// numeric segment on an object is used as a key. Any other value that can't be
+    /// navigated by the next segment (such as a scalar) is **overwritten**.
+    /// ```
+    /// # use bourne::Value;
+    /// let mut value = Value::Null;
+    /// *value.entry_path("a.b.c") = 5i64.into();
+    /// assert_eq!(value["a"]["b"]["c"], Value::from(5i64));
+    /// ```
+    pub fn entry_path(&mut self, path: &str) -> &mut Value {
+        path.split('.').fold(self, |value, segment| {
+            let index = segment.parse::<usize>().ok()
+                .filter(|_| segment.bytes().all(|b| b.is_ascii_digit()));
+            match (index, &*value) {
+                (_, Value::Object(_)) => (),
+                (Some(_), Value::Array(_)) => (),
+                (Some(_), _) => *value = Value::Array(Vec::new()),
+                (None, _) => *value = Value::Object(ValueMap::new()),
+            }
+            match (index, value) {
+                (_, Value::Object(object)) => object.entry(Key::from(segment)).or_insert(Value::Null),
+                (Some(index), Value::Array(array)) => {
+                    if array.len() <= index {
+                        array.push(Value::Null);
+                    }
+                    let last = array.len() - 1;
+                    &mut array[index.min(last)]
+                }
+                _ => unreachable!(),
+            }
+        })
+    }
+
+    /// Remove `key` from a [Value::Object], returning its value if it was present.
+    /// Returns [None] if self is not a [Value::Object].
+    /// 
+    /// With `preserve_order`, this uses `IndexMap::shift_remove` so the
+    /// remaining keys keep their relative order (at the cost of being O(n)).
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
         let Value::Object(object) = self else {
-            panic!("Not an object.");
+            return None;
         };
-        k.insert_into(object, v.into())
+        #[cfg(feature = "preserve_order")]
+        return object.shift_remove(key);
+        #[cfg(not(feature = "preserve_order"))]
+        return object.remove(key);
     }
 
-    /// Get an immutable reference to a [Value] by index or key.
-    pub fn get<I: IndexOrKey>(&self, i_k: I) -> Option<&Value> {
-        i_k.get(self)
+    /// Remove the element at `index` from a [Value::Array], shifting the following
+    /// elements down. Returns [None] if self is not a [Value::Array] or `index` is out of range.
+    pub fn remove_index(&mut self, index: usize) -> Option<Value> {
+        let Value::Array(array) = self else {
+            return None;
+        };
+        if index < array.len() {
+            Some(array.remove(index))
+        } else {
+            None
+        }
     }
 
-    /// Get a mutable reference to a [Value] by index or key.
-    pub fn get_mut<I: IndexOrKey>(&mut self, i_k: I) -> Option<&mut Value> {
-        i_k.get_mut(self)
+    /// Keep only the elements of a [Value::Array] for which `f` returns true, preserving
+    /// their order. Does nothing if self is not a [Value::Array].
+    pub fn retain_array<F: FnMut(&Value) -> bool>(&mut self, f: F) {
+        if let Value::Array(array) = self {
+            array.retain(f);
+        }
     }
 
-    /// Get the length of the [Value] if it is one of the following variants:
-    /// * [Value::String]
-    /// * [Value::Array]
-    /// * [Value::Object]
-    pub fn len(&self) -> usize {
+    /// Keep only the entries of a [Value::Object] for which `f` returns true.
+    /// Does nothing if self is not a [Value::Object].
+    pub fn retain_object<F: FnMut(&str, &Value) -> bool>(&mut self, mut f: F) {
+        if let Value::Object(object) = self {
+            object.retain(|key, value| f(key, value));
+        }
+    }
+
+    /// Recursively remove [Value::Null] entries from every object in the tree.
+    ///
+    /// With `arrays`, [Value::Null] array elements are removed too. With `empty_objects`,
+    /// objects that are empty once pruned are removed as well, in the same places nulls
+    /// are. Self is never removed, only emptied.
+    pub fn prune_nulls(&mut self, arrays: bool, empty_objects: bool) {
+        fn prunable(value: &Value, empty_objects: bool) -> bool {
+            match value {
+                Value::Null => true,
+                Value::Object(object) => empty_objects && object.is_empty(),
+                _ => false,
+            }
+        }
         match self {
-            Value::String(string) => string.len(),
-            Value::Array(array) => array.len(),
-            Value::Object(object) => object.len(),
-            _ => 0,
+            Value::Array(array) => {
+                array.iter_mut().for_each(|value| value.prune_nulls(arrays, empty_objects));
+                if arrays {
+                    array.retain(|value| !prunable(value, empty_objects));
+                }
+            }
+            Value::Object(object) => {
+                object.values_mut().for_each(|value| value.prune_nulls(arrays, empty_objects));
+                object.retain(|_, value| !prunable(value, empty_objects));
+            }
+            _ => (),
         }
     }
-}
 
-impl<I: IndexOrKey> std::ops::Index<I> for Value {
-    type Output = Value;
-    fn index(&self, index: I) -> &Self::Output {
-        static NULL: Value = Value::Null;
-        index.get(self).unwrap_or(&NULL)
+    /// Returns true if self is a [Value::Object] containing `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        match self {
+            Value::Object(object) => object.contains_key(key),
+            _ => false,
+        }
     }
-}
 
-impl<I: IndexOrKey> std::ops::IndexMut<I> for Value {
-    fn index_mut(&mut self, index: I) -> &mut Self::Output {
-        index.get_or_insert(self)
+    /// Iterate over the keys of a [Value::Object]. Yields nothing for other variants.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        let object = match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        };
+        object.into_iter().flat_map(|object| object.keys().map(|key| &**key))
     }
-}
 
-pub trait ArrayExt {
-    fn push_value<T: Into<Value>>(&mut self, value: T);
-}
+    /// Get the keys of a [Value::Object] in iteration order, or [None] for other variants.
+    /// 
+    /// With `preserve_order` this is insertion order, which for parsed objects is the
+    /// order the keys appear in the source (and the order they are serialized in).
+    /// Without it, the order is unspecified.
+    pub fn object_keys_in_order(&self) -> Option<Vec<&str>> {
+        match self {
+            Value::Object(object) => Some(object.keys().map(|key| &**key).collect()),
+            _ => None,
+        }
+    }
 
-impl ArrayExt for Vec<Value> {
-    fn push_value<T: Into<Value>>(&mut self, value: T) {
-        self.push(value.into());
+    /// Get the entries of a [Value::Object] sorted by key (byte-wise), independent of the
+    /// map's iteration order. Returns an empty [Vec] for other variants.
+    pub fn sorted_entries(&self) -> Vec<(&str, &Value)> {
+        let Value::Object(object) = self else {
+            return Vec::new();
+        };
+        let mut entries = object.iter().map(|(key, value)| (&**key, value)).collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        entries
     }
-}
+
+    /// Sort the keys of every object in the tree lexicographically (byte-wise), so they
+    /// are iterated and serialized in that order.
+    /// 
+    /// Only has an effect with `preserve_order`. Without it, the order is unspecified
+    /// and nothing is changed.
+    pub fn sort_keys_recursive(&mut self) {
+        match self {
+            Value::Array(array) => array.iter_mut().for_each(Value::sort_keys_recursive),
+            Value::Object(object) => {
+                #[cfg(feature = "preserve_order")]
+                object.sort_keys();
+                object.values_mut().for_each(Value::sort_keys_recursive);
+            }
+            _ => (),
+        }
+    }
+
+    /// Apply `f` to every node in the tree in depth-first pre-order: first to self, then
+    /// to each array element or object value in turn. Containers are visited too, and
+    /// the children visited are those of the container after `f` has been applied to it.
+    pub fn map_values<F: FnMut(&mut Value)>(&mut self, mut f: F) {
+        fn visit<F: FnMut(&mut Value)>(value: &mut Value, f: &mut F) {
+            f(value);
+            match value {
+                Value::Array(array) => array.iter_mut().for_each(|value| visit(value, f)),
+                Value::Object(object) => object.values_mut().for_each(|value| visit(value, f)),
+                _ => (),
+            }
+        }
+        visit(self, &mut f);
+    }
+
+    /// Build a transformed copy of the tree, leaving self untouched. `f` is called with each
+    /// node in depth-first pre-order: returning `Some` puts that value in its place (without
+    /// visiting its children), and returning [None] copies the node, recursing into arrays
+    /// and objects. See [Value::map_values] for the in-place equivalent.
+    pub fn transform<F: Fn(&Value) -> Option<Value>>(&self, f: F) -> Value {
+        fn visit<F: Fn(&Value) -> Option<Value>>(value: &Value, f: &F) -> Value {
+            if let Some(replacement) = f(value) {
+                return replacement;
+            }
+            match value {
+                Value::Array(array) => Value::Array(array.iter().map(|value| visit(value, f)).collect()),
+                Value::Object(object) => Value::Object(object.iter().map(|(key, value)| (key.clone(), visit(value, f))).collect()),
+                scalar => scalar.clone(),
+            }
+        }
+        visit(self, &f)
+    }
+
+    /// Apply `f` to the map of every [Value::Object] in the tree, in depth-first pre-order,
+    /// for edits such as renaming a key everywhere. See [Value::map_values].
+    pub fn for_each_object_mut<F: FnMut(&mut ValueMap)>(&mut self, mut f: F) {
+        self.map_values(|value| {
+            if let Value::Object(object) = value {
+                f(object);
+            }
+        });
+    }
+
+    /// Call `f` with every node in the tree in depth-first pre-order. See [Value::map_values].
+    pub fn for_each_value<'a, F: FnMut(&'a Value)>(&'a self, mut f: F) {
+        fn visit<'a, F: FnMut(&'a Value)>(value: &'a Value, f: &mut F) {
+            f(value);
+            match value {
+                Value::Array(array) => array.iter().for_each(|value| visit(value, f)),
+                Value::Object(object) => object.values().for_each(|value| visit(value, f)),
+                _ => (),
+            }
+        }
+        visit(self, &mut f);
+    }
+
+    /// Sum a [Value::Array] of numbers. The sum is a [Number::Int] (or [Number::Uint] if it
+    /// only fits in a [u64]) when every element is an integer, and a [Number::Float] if
+    /// any element is a float or the integer sum overflows. An empty array sums to `0`.
+    ///
+    /// Returns [None] if self is not a [Value::Array] or any element is not a number.
+    pub fn sum_numbers(&self) -> Option<Number> {
+        let numbers = self.as_array()?.iter()
+            .map(|value| value.as_number().map(Number::resolve))
+            .collect::<Option<Vec<_>>>()?;
+        let int_sum = numbers.iter().try_fold(0i128, |sum, number| sum.checked_add(number.integer()?));
+        let int_sum = int_sum.and_then(|sum| match i64::try_from(sum) {
+            Ok(int) => Some(Number::Int(int)),
+            Err(_) => u64::try_from(sum).ok().map(Number::Uint),
+        });
+        Some(int_sum.unwrap_or_else(|| Number::Float(numbers.iter().map(|number| number.as_f64()).sum())))
+    }
+
+    /// The mean of a [Value::Array] of numbers, as an [f64].
+    ///
+    /// Returns [None] if self is not a non-empty [Value::Array] or any element is not a number.
+    pub fn average(&self) -> Option<f64> {
+        let len = self.as_array()?.len();
+        if len == 0 {
+            return None;
+        }
+        Some(self.sum_numbers()?.as_f64() / len as f64)
+    }
+
+    /// Convert every number in the tree to a [Number::Float], for uniform arithmetic.
+    /// Integers beyond 2^53 lose precision.
+    pub fn numbers_to_f64(&mut self) {
+        self.map_values(|value| {
+            if let Value::Number(number) = value {
+                *number = Number::Float(number.as_f64());
+            }
+        });
+    }
+
+    /// Convert every integral [Number::Float] in the tree to a [Number::Int], or a
+    /// [Number::Uint] if it only fits in a [u64]. Floats with a fractional part, floats
+    /// outside the [u64] and [i64] ranges, and `-0.0` are left as they are.
+    ///
+    /// A [Number::Raw] written with a fraction or exponent, such as `2.0` or `1e2`, is
+    /// converted the same way when its text is exactly an integer, as with the
+    /// `arbitrary_precision` feature.
+    pub fn normalize_integral_floats(&mut self) {
+        self.map_values(|value| {
+            let integral = match value {
+                &mut Value::Number(Number::Float(float)) => Number::integral(float),
+                Value::Number(Number::Raw(text)) if text.contains(['.', 'e', 'E']) => Number::integral_text(text),
+                _ => None,
+            };
+            if let Some(number) = integral {
+                *value = Value::Number(number);
+            }
+        });
+    }
+
+    /// Iterate over the values of a [Value::Object]. Yields nothing for other variants.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        let object = match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        };
+        object.into_iter().flat_map(|object| object.values())
+    }
+
+    /// Get an immutable reference to a [Value] by index or key.
+    pub fn get<I: IndexOrKey>(&self, i_k: I) -> Option<&Value> {
+        i_k.get(self)
+    }
+
+    /// Get a [Value] by index or key, or `default` if it is missing. A present
+    /// [Value::Null] is returned as is, so unlike indexing, absent and null can be told apart.
+    pub fn get_or<'a, I: IndexOrKey>(&'a self, i_k: I, default: &'a Value) -> &'a Value {
+        self.get(i_k).unwrap_or(default)
+    }
+
+    /// Get a [Value] by index or key, or [Value::Null] if it is missing. Same as indexing,
+    /// but explicit about the fallback.
+    pub fn get_or_null<I: IndexOrKey>(&self, i_k: I) -> &Value {
+        static NULL: Value = Value::Null;
+        self.get_or(i_k, &NULL)
+    }
+
+    /// Tell apart a missing key, a key explicitly set to null, and a key with a value,
+    /// which indexing and [Value::get_or_null] both report as [Value::Null].
+    pub fn get_field_status(&self, key: &str) -> FieldStatus<'_> {
+        match self.get(key) {
+            None => FieldStatus::Absent,
+            Some(Value::Null) => FieldStatus::Null,
+            Some(value) => FieldStatus::Present(value),
+        }
+    }
+
+    /// Get an [i64] by index or key (see [Value::as_i64]), or `default` if it is missing
+    /// or not an integer.
+    pub fn get_i64_or<I: IndexOrKey>(&self, i_k: I, default: i64) -> i64 {
+        self.get(i_k).and_then(Value::as_i64).unwrap_or(default)
+    }
+
+    /// Get a [str] by index or key, or `default` if it is missing or not a string.
+    pub fn get_str_or<'a, I: IndexOrKey>(&'a self, i_k: I, default: &'a str) -> &'a str {
+        self.get(i_k).and_then(Value::as_str).unwrap_or(default)
+    }
+
+    /// Get a [bool] by index or key, or `default` if it is missing or not a boolean.
+    pub fn get_bool_or<I: IndexOrKey>(&self, i_k: I, default: bool) -> bool {
+        self.get(i_k).and_then(Value::as_bool).unwrap_or(default)
+    }
+
+    /// Get a mutable reference to a [Value] by index or key.
+    pub fn get_mut<I: IndexOrKey>(&mut self, i_k: I) -> Option<&mut Value> {
+        i_k.get_mut(self)
+    }
+
+    /// Deep-merge `other` into self.
+    /// 
+    /// When both self and `other` are [Value::Object]s, each key in `other` is merged
+    /// into the matching key in self (recursively). In every other case `other` replaces
+    /// self, so scalars and arrays from `other` take precedence.
+    pub fn merge(&mut self, other: Value) {
+        match (self, other) {
+            (Value::Object(object), Value::Object(other)) => {
+                for (key, value) in other {
+                    match object.get_mut(&key) {
+                        Some(existing) => existing.merge(value),
+                        None => { object.insert(key, value); }
+                    }
+                }
+            }
+            (this, other) => *this = other,
+        }
+    }
+
+    /// Estimate the memory used by the [Value] in bytes: the [Value] itself plus
+    /// everything it owns on the heap, such as string and array capacities and the
+    /// entries of objects. Allocator and hash table overhead is approximated.
+    pub fn byte_size(&self) -> usize {
+        core::mem::size_of::<Value>() + self.heap_size()
+    }
+
+    /// Visit every node in depth-first pre-order, with the number of arrays and objects
+    /// enclosing it. Uses an explicit stack, so deep trees can't overflow the call stack.
+    fn nodes_with_depth(&self) -> impl Iterator<Item = (&Value, usize)> {
+        let mut stack = vec![(self, 0)];
+        core::iter::from_fn(move || {
+            let (value, depth) = stack.pop()?;
+            match value {
+                Value::Array(array) => stack.extend(array.iter().rev().map(|value| (value, depth + 1))),
+                Value::Object(object) => stack.extend(object.values().map(|value| (value, depth + 1))),
+                _ => (),
+            }
+            Some((value, depth))
+        })
+    }
+
+    /// The total number of values in the tree, counting arrays and objects as well as
+    /// their contents, and self. Object keys are not counted.
+    pub fn node_count(&self) -> usize {
+        self.nodes_with_depth().count()
+    }
+
+    /// How deeply arrays and objects are nested: `0` for a scalar, `1` for an array or
+    /// object of scalars, and so on. This is the smallest
+    /// [ParseOptions::max_depth](parse::ParseOptions::max_depth) the value would parse with.
+    pub fn max_depth(&self) -> usize {
+        self.nodes_with_depth()
+            .map(|(value, depth)| match value {
+                Value::Array(_) | Value::Object(_) => depth + 1,
+                _ => depth,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The heap memory owned by the [Value], not counting the [Value] itself.
+    fn heap_size(&self) -> usize {
+        match self {
+            Value::Number(Number::Raw(text)) => text.capacity(),
+            Value::Null | Value::Boolean(_) | Value::Number(_) => 0,
+            Value::String(string) => string.capacity(),
+            Value::Array(array) => {
+                array.capacity() * core::mem::size_of::<Value>()
+                    + array.iter().map(Value::heap_size).sum::<usize>()
+            }
+            Value::Object(object) => {
+                // Each slot holds a key and a value, plus roughly a hash or control byte.
+                let slot = core::mem::size_of::<(Key, Value)>() + core::mem::size_of::<usize>();
+                // Shared keys are counted in full for every object they appear in.
+                #[cfg(feature = "shared_keys")]
+                let key_size = |key: &Key| key.len();
+                #[cfg(not(feature = "shared_keys"))]
+                let key_size = |key: &Key| key.capacity();
+                object.capacity() * slot
+                    + object.iter().map(|(key, value)| key_size(key) + value.heap_size()).sum::<usize>()
+            }
+        }
+    }
+
+    /// Get the length of the [Value] if it is one of the following variants:
+    /// * [Value::String]
+    /// * [Value::Array]
+    /// * [Value::Object]
+    /// 
+    /// Returns 0 for all other variants. Use [Value::is_empty] to tell an empty container
+    /// apart from a [Value::Boolean] or [Value::Number].
+    pub fn len(&self) -> usize {
+        match self {
+            Value::String(string) => string.len(),
+            Value::Array(array) => array.len(),
+            Value::Object(object) => object.len(),
+            _ => 0,
+        }
+    }
+
+    /// Returns true if the [Value] holds nothing:
+    /// * [Value::Null] is always empty.
+    /// * [Value::String], [Value::Array] and [Value::Object] are empty when their length is 0.
+    /// * [Value::Boolean] and [Value::Number] are never empty, as they always hold a value.
+    ///   Note that this differs from `len() == 0`.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Value::Null => true,
+            Value::Boolean(_) | Value::Number(_) => false,
+            Value::String(string) => string.is_empty(),
+            Value::Array(array) => array.is_empty(),
+            Value::Object(object) => object.is_empty(),
+        }
+    }
+}
+
+impl<I: IndexOrKey> core::ops::Index<I> for Value {
+    type Output = Value;
+    fn index(&self, index: I) -> &Self::Output {
+        static NULL: Value = Value::Null;
+        index.get(self).unwrap_or(&NULL)
+    }
+}
+
+impl<I: IndexOrKey> core::ops::IndexMut<I> for Value {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        index.get_or_insert(self)
+    }
+}
+
+impl core::ops::Add for Value {
+    type Output = Value;
+    /// Combine two [Value]s:
+    /// * [Value::Array] + [Value::Array] concatenates the arrays.
+    /// * [Value::Object] + [Value::Object] is the union of the objects. Keys from the
+    ///   right-hand side replace those on the left.
+    /// * [Value::Null] + `x` and `x` + [Value::Null] are `x`.
+    /// * Any other combination creates a two-element [Value::Array] `[lhs, rhs]`.
+    fn add(self, rhs: Value) -> Self::Output {
+        match (self, rhs) {
+            (Value::Array(mut lhs), Value::Array(rhs)) => {
+                lhs.extend(rhs);
+                Value::Array(lhs)
+            }
+            (Value::Object(mut lhs), Value::Object(rhs)) => {
+                lhs.extend(rhs);
+                Value::Object(lhs)
+            }
+            (Value::Null, other) | (other, Value::Null) => other,
+            (lhs, rhs) => Value::Array(vec![lhs, rhs]),
+        }
+    }
+}
+
+impl core::ops::AddAssign for Value {
+    /// Combine `rhs` into self. See [Value::add](std::ops::Add::add) for the rules.
+    fn add_assign(&mut self, rhs: Value) {
+        *self = core::mem::take(self) + rhs;
+    }
+}
+
+pub trait ArrayExt {
+    fn push_value<T: Into<Value>>(&mut self, value: T);
+}
+
+impl ArrayExt for Vec<Value> {
+    fn push_value<T: Into<Value>>(&mut self, value: T) {
+        self.push(value.into());
+    }
+}
 
 pub trait ObjectExt {
     fn insert_value<T: Into<Value>>(&mut self, key: String, value: T) -> Option<Value>;
@@ -337,13 +1309,14 @@ pub trait ObjectExt {
 
 impl ObjectExt for ValueMap {
     fn insert_value<T: Into<Value>>(&mut self, k: String, v: T) -> Option<Value> {
-        self.insert(k, v.into())
+        self.insert(into_key(k), v.into())
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
-    use std::str::FromStr;
+    use core::str::FromStr;
 
     use super::*;
     #[test]
@@ -354,10 +1327,786 @@ mod tests {
                 "float": 3.14159265358979
             }
         "#)?;
+        #[cfg(not(feature = "arbitrary_precision"))]
         assert!(matches!(object["int"], Value::Number(Number::Int(i64::MAX))));
+        #[cfg(not(feature = "arbitrary_precision"))]
         assert!(matches!(object["float"], Value::Number(Number::Float(3.14159265358979))));
+        #[cfg(feature = "arbitrary_precision")]
+        assert_eq!(object["int"], i64::MAX);
+        #[cfg(feature = "arbitrary_precision")]
+        assert_eq!(object["float"].as_f64(), "3.14159265358979".parse().ok());
         let json_text = object.to_string();
         assert_eq!(json_text, r#"{"int":9223372036854775807,"float":3.14159265358979}"#);
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn parse_structure_test() -> Result<(), crate::error::ParseError> {
+        let object = Value::from_str(r#"
+            {
+                "zeta": 1,
+                "alpha": [true, null, "two"],
+                "mid": { "y": 2.5, "x": {} },
+                "alpha": "replaced",
+                "beta": false
+            }
+        "#)?;
+        let Value::Object(map) = &object else {
+            panic!("Not an object.");
+        };
+        let mut keys = map.keys().map(|key| &**key).collect::<Vec<_>>();
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(keys, ["zeta", "alpha", "mid", "beta"]);
+        keys.sort_unstable();
+        assert_eq!(keys, ["alpha", "beta", "mid", "zeta"]);
+        #[cfg(not(feature = "arbitrary_precision"))]
+        assert!(matches!(object["zeta"], Value::Number(Number::Int(1))));
+        #[cfg(feature = "arbitrary_precision")]
+        assert!(object["zeta"].strict_eq(&Value::from(1i64)));
+        assert!(matches!(&object["alpha"], Value::String(s) if s == "replaced"));
+        #[cfg(not(feature = "arbitrary_precision"))]
+        assert!(matches!(object["mid"]["y"], Value::Number(Number::Float(y)) if y == 2.5));
+        #[cfg(feature = "arbitrary_precision")]
+        assert_eq!(object["mid"]["y"], 2.5);
+        assert!(matches!(&object["mid"]["x"], Value::Object(x) if x.is_empty()));
+        assert!(matches!(object["beta"], Value::Boolean(false)));
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(object.to_string(), r#"{"zeta":1,"alpha":"replaced","mid":{"y":2.5,"x":{}},"beta":false}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_test() -> Result<(), crate::error::ParseError> {
+        let mut config = Value::from_str(r#"
+            {
+                "name": "base",
+                "server": { "host": "localhost", "port": 80, "tls": { "enabled": false } },
+                "tags": ["a", "b"]
+            }
+        "#)?;
+        config.merge(Value::from_str(r#"
+            {
+                "server": { "port": 8080, "tls": { "cert": "cert.pem" } },
+                "tags": ["c"],
+                "debug": true
+            }
+        "#)?);
+        let expected = Value::from_str(r#"
+            {
+                "name": "base",
+                "server": { "host": "localhost", "port": 8080, "tls": { "enabled": false, "cert": "cert.pem" } },
+                "tags": ["c"],
+                "debug": true
+            }
+        "#)?;
+        assert_eq!(config, expected);
+        let mut scalar = Value::from(1i64);
+        scalar.merge(Value::from("overwritten"));
+        assert_eq!(scalar, Value::from("overwritten"));
+        Ok(())
+    }
+
+    #[test]
+    fn number_test() {
+        assert_eq!(Number::Int(5).to_string(), "5");
+        assert_eq!(Number::Float(3.14).to_string(), "3.14");
+        assert_eq!(Number::Float(3.0).to_string(), "3.0");
+        assert_eq!(Number::Float(3.5).as_f64(), 3.5);
+        assert_eq!(Number::Int(-7).as_f64(), -7.0);
+        assert_eq!(Number::Float(3.0).as_i64(), Some(3));
+        assert_eq!(Number::Float(3.5).as_i64(), None);
+        assert_eq!(Number::Float(1e20).as_i64(), None);
+        assert_eq!(Number::Int(i64::MIN).as_i64(), Some(i64::MIN));
+    }
+
+    #[test]
+    fn builder_test() -> Result<(), crate::error::ParseError> {
+        let built = Value::object()
+            .with("name", "Fred")
+            .with("age", 197i64)
+            .with("classes", Value::array().with_item("Algebra").with_item(3.5))
+            .with("rgb", Value::object().with("r", 4i64).with("g", Value::Null));
+        let parsed = Value::from_str(r#"
+            {
+                "name": "Fred",
+                "age": 197,
+                "classes": ["Algebra", 3.5],
+                "rgb": { "r": 4, "g": null }
+            }
+        "#)?;
+        assert_eq!(built, parsed);
+        Ok(())
+    }
+
+    #[test]
+    fn mut_or_insert_test() -> Result<(), crate::error::ParseError> {
+        let mut value = Value::Null;
+        value.as_object_mut_or_insert().insert("a".into(), Value::from(1i64));
+        value.as_object_mut_or_insert().insert("b".into(), Value::Null);
+        value["b"].as_array_mut_or_insert().push(Value::from(true));
+        value["b"].as_array_mut_or_insert().push(Value::from(false));
+        assert_eq!(value, Value::from_str(r#"{"a": 1, "b": [true, false]}"#)?);
+
+        let mut existing = Value::from_str(r#"{"a": {"x": 1}, "list": [1]}"#)?;
+        existing["a"].as_object_mut_or_insert().insert("y".into(), Value::from(2i64));
+        existing["list"].as_array_mut_or_insert().clear();
+        assert_eq!(existing, Value::from_str(r#"{"a": {"x": 1, "y": 2}, "list": []}"#)?);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected object or null, found string.")]
+    fn mut_or_insert_scalar_test() {
+        Value::from("text").as_object_mut_or_insert();
+    }
+
+    #[test]
+    fn node_count_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "tag": null,
+                "name": "Fred",
+                "classes": ["Algebra", "History of Programming"],
+                "rgb_for_some_reason": { "r": 4, "g": "seven", "b": [] }
+            }
+        "#)?;
+        assert_eq!(value.node_count(), 10);
+        assert_eq!(value.max_depth(), 3);
+        assert_eq!(value["classes"].node_count(), 3);
+        assert_eq!(value["classes"].max_depth(), 1);
+        assert_eq!(Value::Null.node_count(), 1);
+        assert_eq!(Value::Null.max_depth(), 0);
+        assert_eq!(Value::array().max_depth(), 1);
+
+        let source = value.to_string();
+        let options = |max_depth| parse::ParseOptions { max_depth: Some(max_depth), ..Default::default() };
+        assert!(Value::from_str_with_options(&source, options(value.max_depth())).is_ok());
+        assert!(Value::from_str_with_options(&source, options(value.max_depth() - 1)).is_err());
+
+        const DEPTH: usize = 100_000;
+        let mut deep = Value::Null;
+        for _ in 0..DEPTH {
+            deep = Value::Array(vec![deep]);
+        }
+        assert_eq!(deep.node_count(), DEPTH + 1);
+        assert_eq!(deep.max_depth(), DEPTH);
+        // Dropping the value recursively would overflow the stack, so take it apart first.
+        while let Value::Array(mut array) = deep {
+            deep = array.pop().unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn remove_test() -> Result<(), crate::error::ParseError> {
+        let mut object = Value::from_str(r#"{"a": 1, "b": [1, 2, 3], "c": 3}"#)?;
+        assert_eq!(object.remove("a"), Some(Value::from(1i64)));
+        assert_eq!(object.remove("a"), None);
+        assert_eq!(object["b"].remove_index(1), Some(Value::from(2i64)));
+        assert_eq!(object["b"].remove_index(2), None);
+        assert_eq!(object["c"].remove_index(0), None);
+        assert_eq!(object, Value::from_str(r#"{"b": [1, 3], "c": 3}"#)?);
+        #[cfg(feature = "preserve_order")]
+        {
+            let mut ordered = Value::from_str(r#"{"x": 1, "y": 2, "z": 3}"#)?;
+            ordered.remove("x");
+            assert_eq!(ordered.to_string(), r#"{"y":2,"z":3}"#);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn keys_values_test() -> Result<(), crate::error::ParseError> {
+        let object = Value::from_str(r#"
+            {
+                "tag": null,
+                "registered": true,
+                "age": 197,
+                "name": "Fred"
+            }
+        "#)?;
+        assert!(object.contains_key("age"));
+        assert!(!object.contains_key("classes"));
+        let mut keys = object.keys().collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert_eq!(keys, ["age", "name", "registered", "tag"]);
+        assert_eq!(object.values().count(), 4);
+        assert!(object.values().any(|value| *value == "Fred"));
+        let array = Value::from(vec![Value::from(1i64)]);
+        assert!(!array.contains_key("0"));
+        assert_eq!(array.keys().count(), 0);
+        assert_eq!(array.values().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn try_from_test() -> Result<(), crate::error::ParseError> {
+        let object = Value::from_str(r#"{"int": 5, "float": 5.5, "bool": true, "string": "Fred"}"#)?;
+        assert_eq!(i64::try_from(&object["int"]), Ok(5));
+        assert_eq!(f64::try_from(&object["float"]), Ok(5.5));
+        assert_eq!(f64::try_from(&object["int"]), Ok(5.0));
+        assert_eq!(bool::try_from(&object["bool"]), Ok(true));
+        assert_eq!(String::try_from(&object["string"]), Ok(String::from("Fred")));
+        assert_eq!(<&str>::try_from(&object["string"]), Ok("Fred"));
+        let error = i64::try_from(&object["float"]).unwrap_err();
+        assert_eq!((error.expected, error.found), ("integer", "number"));
+        let error = bool::try_from(&object["missing"]).unwrap_err();
+        assert_eq!((error.expected, error.found), ("boolean", "null"));
+        let error = <&str>::try_from(&object).unwrap_err();
+        assert_eq!((error.expected, error.found), ("string", "object"));
+        assert_eq!(error.to_string(), "Expected string, found object.");
+        Ok(())
+    }
+
+    #[test]
+    fn type_name_test() {
+        assert_eq!(Value::Null.type_name(), "null");
+        assert_eq!(Value::from(false).type_name(), "boolean");
+        assert_eq!(Value::from(1i64).type_name(), "number");
+        assert_eq!(Value::from(1.5).type_name(), "number");
+        assert_eq!(Value::from("text").type_name(), "string");
+        assert_eq!(Value::array().type_name(), "array");
+        assert_eq!(Value::object().type_name(), "object");
+    }
+
+    #[test]
+    fn entry_test() {
+        let mut counts = Value::Null;
+        for word in "the quick fox and the lazy dog and the cat".split(' ') {
+            let count = counts.entry(word).or_insert(Value::from(0i64));
+            if let Value::Number(Number::Int(count)) = count {
+                *count += 1;
+            }
+        }
+        assert_eq!(counts.len(), 7);
+        assert_eq!(counts["the"], Value::from(3i64));
+        assert_eq!(counts["and"], Value::from(2i64));
+        assert_eq!(counts["fox"], Value::from(1i64));
+        counts.entry(String::from("seen")).or_insert_with(Value::array).push("fox");
+        assert_eq!(counts["seen"], Value::from(vec![Value::from("fox")]));
+    }
+
+    #[test]
+    fn raw_number_test() {
+        let raw = |text: &str| Number::Raw(text.to_string());
+        assert_eq!(raw("-12").as_i64(), Some(-12));
+        assert_eq!(raw("18446744073709551615").as_u64(), Some(u64::MAX));
+        assert_eq!(raw("1e2").as_i64(), Some(100));
+        assert_eq!(raw("0.5").as_f64(), 0.5);
+        // `-0` keeps its sign, as it does when parsed without keeping the text.
+        assert!(raw("-0").as_f64().is_sign_negative());
+        assert_eq!(raw("-0").as_i64(), Some(0));
+        assert!(raw("not a number").as_f64().is_nan());
+    }
+
+    #[test]
+    fn uint_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str("18446744073709551615")?;
+        #[cfg(not(feature = "arbitrary_precision"))]
+        assert!(matches!(value, Value::Number(Number::Uint(u64::MAX))));
+        #[cfg(feature = "arbitrary_precision")]
+        assert_eq!(value, u64::MAX);
+        assert_eq!(value.to_string(), "18446744073709551615");
+        assert_eq!(Value::from(u64::MAX), value);
+        assert!(matches!(Value::from(5u64), Value::Number(Number::Int(5))));
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            assert!(matches!(Value::from_str("9223372036854775807")?, Value::Number(Number::Int(i64::MAX))));
+            assert!(matches!(Value::from_str("9223372036854775808")?, Value::Number(Number::Uint(9223372036854775808))));
+            assert!(Value::from_str("18446744073709551616").is_err());
+            assert!(Value::from_str("-9223372036854775809").is_err());
+        }
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            assert_eq!(Value::from_str("9223372036854775807")?.as_i64(), Some(i64::MAX));
+            let above_i64 = Value::from_str("9223372036854775808")?;
+            assert_eq!((above_i64.as_i64(), above_i64.as_u64()), (None, Some(9223372036854775808)));
+            // Integers of any size are kept as text.
+            assert!(Value::from_str("18446744073709551616").is_ok());
+            assert!(Value::from_str("-9223372036854775809").is_ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn default_test() {
+        assert!(matches!(Value::default(), Value::Null));
+        assert!(matches!(Number::default(), Number::Int(0)));
+        let mut value = Value::from("taken");
+        assert_eq!(core::mem::take(&mut value), Value::from("taken"));
+        assert!(matches!(value, Value::Null));
+    }
+
+    #[test]
+    fn entry_path_test() -> Result<(), crate::error::ParseError> {
+        let mut value = Value::Null;
+        *value.entry_path("server.tls.enabled") = true.into();
+        *value.entry_path("server.hosts.0") = "x".into();
+        *value.entry_path("server.hosts.1") = "b".into();
+        *value.entry_path("server.hosts.0") = "a".into();
+        *value.entry_path("server.port") = 80i64.into();
+        assert_eq!(value, Value::from_str(r#"
+            {
+                "server": {
+                    "tls": { "enabled": true },
+                    "hosts": ["a", "b"],
+                    "port": 80
+                }
+            }
+        "#)?);
+        // Scalars on the way are overwritten.
+        *value.entry_path("server.port.number") = 8080i64.into();
+        assert_eq!(value["server"]["port"], Value::from_str(r#"{"number": 8080}"#)?);
+        // Indices past the end append instead of padding.
+        *value.entry_path("server.hosts.4294967296") = "c".into();
+        *value.entry_path("server.hosts.18446744073709551615") = "d".into();
+        assert_eq!(value["server"]["hosts"], Value::from_str(r#"["a", "b", "c", "d"]"#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn numeric_accessors_test() -> Result<(), crate::error::ParseError> {
+        let array = Value::from_str("[5, 5.0, 5.5, -1, 3000000000, 18446744073709551615, \"5\"]")?;
+        assert_eq!(array[0].as_number(), Some(&Number::Int(5)));
+        assert_eq!(array[6].as_number(), None);
+        assert_eq!(array[1].as_i64(), Some(5));
+        assert_eq!(array[2].as_i64(), None);
+        assert_eq!(array[1].as_u64(), Some(5));
+        assert_eq!(array[3].as_u64(), None);
+        assert_eq!(array[5].as_u64(), Some(u64::MAX));
+        assert_eq!(array[0].as_usize(), Some(5));
+        assert_eq!(array[3].as_usize(), None);
+        assert_eq!(array[1].as_i32(), Some(5));
+        assert_eq!(array[3].as_i32(), Some(-1));
+        assert_eq!(array[4].as_i32(), None);
+        assert_eq!(array[4].as_i64(), Some(3000000000));
+        assert_eq!(array[6].as_i32(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn is_empty_test() -> Result<(), crate::error::ParseError> {
+        assert!(Value::array().is_empty());
+        assert!(Value::object().is_empty());
+        assert!(Value::from("").is_empty());
+        assert!(Value::Null.is_empty());
+        assert!(!Value::from_str(r#"{"a": null}"#)?.is_empty());
+        assert!(!Value::from_str("[null]")?.is_empty());
+        let number = Value::from(0i64);
+        assert_eq!(number.len(), 0);
+        assert!(!number.is_empty());
+        assert!(!Value::from(false).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn object_keys_in_order_test() -> Result<(), crate::error::ParseError> {
+        let source = r#"{"zulu":1,"alpha":2,"mike":{"yankee":3,"bravo":4},"charlie":5}"#;
+        let object = Value::from_str(source)?;
+        let mut keys = object.object_keys_in_order().unwrap();
+        assert_eq!(object["mike"].object_keys_in_order().unwrap().len(), 2);
+        assert_eq!(Value::array().object_keys_in_order(), None);
+        #[cfg(feature = "preserve_order")]
+        {
+            assert_eq!(keys, ["zulu", "alpha", "mike", "charlie"]);
+            assert_eq!(object["mike"].object_keys_in_order(), Some(vec!["yankee", "bravo"]));
+            assert_eq!(object.to_string(), source);
+        }
+        keys.sort_unstable();
+        assert_eq!(keys, ["alpha", "charlie", "mike", "zulu"]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn sort_keys_recursive_test() -> Result<(), crate::error::ParseError> {
+        let mut object = Value::from_str(r#"{"zulu":1,"alpha":[{"yankee":3,"bravo":4}],"mike":{"x":5,"b":6}}"#)?;
+        object.sort_keys_recursive();
+        assert_eq!(object.object_keys_in_order(), Some(vec!["alpha", "mike", "zulu"]));
+        assert_eq!(object["alpha"][0].object_keys_in_order(), Some(vec!["bravo", "yankee"]));
+        assert_eq!(object.to_string(), r#"{"alpha":[{"bravo":4,"yankee":3}],"mike":{"b":6,"x":5},"zulu":1}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn sorted_entries_test() {
+        let object = Value::object()
+            .with("mike", 1i64)
+            .with("alpha", true)
+            .with("zulu", Value::Null)
+            .with("charlie", "c");
+        assert_eq!(object.sorted_entries(), vec![
+            ("alpha", &Value::from(true)),
+            ("charlie", &Value::from("c")),
+            ("mike", &Value::from(1i64)),
+            ("zulu", &Value::Null),
+        ]);
+        assert!(Value::array().with_item(1i64).sorted_entries().is_empty());
+    }
+
+    #[test]
+    fn transform_test() -> Result<(), crate::error::ParseError> {
+        let original = Value::from_str(r#"
+            {
+                "name": "Fred",
+                "age": 197,
+                "classes": ["Algebra", 3.5, {"room": 101, "teacher": "Wilma"}],
+                "card": {"number": 4111111111111111, "holder": "Fred"}
+            }
+        "#)?;
+        let nulled = original.transform(|value| match value {
+            Value::Number(_) => Some(Value::Null),
+            _ => None,
+        });
+        assert_eq!(nulled, Value::from_str(r#"
+            {
+                "name": "Fred",
+                "age": null,
+                "classes": ["Algebra", null, {"room": null, "teacher": "Wilma"}],
+                "card": {"number": null, "holder": "Fred"}
+            }
+        "#)?);
+        assert_eq!(original["age"], Value::from(197i64));
+
+        // Replacing a container skips its children.
+        let redacted = original.transform(|value| value.get("number").map(|_| Value::from("[redacted]")));
+        assert_eq!(redacted["card"], Value::from("[redacted]"));
+        assert_eq!(redacted["classes"], original["classes"]);
+        Ok(())
+    }
+
+    #[test]
+    fn for_each_object_mut_test() -> Result<(), crate::error::ParseError> {
+        let mut value = Value::from_str(r#"
+            {
+                "name": "Fred",
+                "rgb_for_some_reason": { "r": 4, "g": 7 },
+                "pets": [
+                    { "name": "Dino", "rgb_for_some_reason": { "r": 128, "g": 0 } },
+                    { "name": "Baby Puss" }
+                ]
+            }
+        "#)?;
+        let mut objects = 0;
+        value.for_each_object_mut(|object| {
+            objects += 1;
+            #[cfg(feature = "preserve_order")]
+            let rgb = object.shift_remove("rgb_for_some_reason");
+            #[cfg(not(feature = "preserve_order"))]
+            let rgb = object.remove("rgb_for_some_reason");
+            if let Some(rgb) = rgb {
+                object.insert("rgb".into(), rgb);
+            }
+        });
+        assert_eq!(objects, 5);
+        assert_eq!(value, Value::from_str(r#"
+            {
+                "name": "Fred",
+                "rgb": { "r": 4, "g": 7 },
+                "pets": [
+                    { "name": "Dino", "rgb": { "r": 128, "g": 0 } },
+                    { "name": "Baby Puss" }
+                ]
+            }
+        "#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn map_values_test() -> Result<(), crate::error::ParseError> {
+        let mut value = Value::from_str(r#"
+            {
+                "tag": null,
+                "name": "Fred",
+                "classes": ["Algebra", "History of Programming"],
+                "rgb_for_some_reason": { "r": 4, "g": "seven" }
+            }
+        "#)?;
+        value.map_values(|value| {
+            if let Value::String(string) = value {
+                *string = string.to_uppercase();
+            }
+        });
+        assert_eq!(value, Value::from_str(r#"
+            {
+                "tag": null,
+                "name": "FRED",
+                "classes": ["ALGEBRA", "HISTORY OF PROGRAMMING"],
+                "rgb_for_some_reason": { "r": 4, "g": "SEVEN" }
+            }
+        "#)?);
+
+        let mut nodes = Vec::new();
+        value["classes"].for_each_value(|value| nodes.push(value.type_name()));
+        assert_eq!(nodes, ["array", "string", "string"]);
+        let mut count = 0;
+        value.for_each_value(|_| count += 1);
+        assert_eq!(count, 9);
+        Ok(())
+    }
+
+    #[test]
+    fn get_or_test() -> Result<(), crate::error::ParseError> {
+        let config = Value::from_str(r#"{"port": 8080, "host": "localhost", "debug": true, "list": [5]}"#)?;
+        assert_eq!(config.get_i64_or("port", 80), 8080);
+        assert_eq!(config.get_i64_or("timeout", 30), 30);
+        assert_eq!(config.get_i64_or("host", 80), 80);
+        assert_eq!(config["list"].get_i64_or(0, -1), 5);
+        assert_eq!(config["list"].get_i64_or(1, -1), -1);
+        assert_eq!(config.get_str_or("host", "0.0.0.0"), "localhost");
+        assert_eq!(config.get_str_or("bind", "0.0.0.0"), "0.0.0.0");
+        assert_eq!(config.get_str_or("port", "0.0.0.0"), "0.0.0.0");
+        assert!(config.get_bool_or("debug", false));
+        assert!(config.get_bool_or("verbose", true));
+        assert!(!config.get_bool_or("host", false));
+
+        let config = Value::from_str(r#"{"port": 8080, "proxy": null, "list": [5]}"#)?;
+        let fallback = Value::from("fallback");
+        assert_eq!(config.get_or("port", &fallback), &Value::from(8080i64));
+        assert_eq!(config.get_or("proxy", &fallback), &Value::Null);
+        assert_eq!(config.get_or("missing", &fallback), &fallback);
+        assert_eq!(config["list"].get_or(0, &fallback), &Value::from(5i64));
+        assert_eq!(config["list"].get_or(1, &fallback), &fallback);
+        assert_eq!(config.get_or_null("port"), &Value::from(8080i64));
+        assert_eq!(config.get_or_null("proxy"), &Value::Null);
+        assert_eq!(config.get_or_null("missing"), &Value::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn field_status_test() -> Result<(), crate::error::ParseError> {
+        let patch = Value::from_str(r#"{"name": "Fred", "email": null, "tags": []}"#)?;
+        assert_eq!(patch.get_field_status("name"), FieldStatus::Present(&Value::from("Fred")));
+        assert_eq!(patch.get_field_status("tags"), FieldStatus::Present(&Value::array()));
+        assert_eq!(patch.get_field_status("email"), FieldStatus::Null);
+        assert_eq!(patch.get_field_status("phone"), FieldStatus::Absent);
+        assert_eq!(patch["email"], patch["phone"]);
+        assert_eq!(patch["tags"].get_field_status("0"), FieldStatus::Absent);
+        assert_eq!(Value::Null.get_field_status("email"), FieldStatus::Absent);
+        Ok(())
+    }
+
+    #[test]
+    fn byte_size_test() -> Result<(), crate::error::ParseError> {
+        let scalar = Value::from(1i64);
+        assert_eq!(scalar.byte_size(), core::mem::size_of::<Value>());
+        let string = Value::from("a".repeat(100));
+        assert!(string.byte_size() >= core::mem::size_of::<Value>() + 100);
+        let small = Value::from_str(r#"{"a": [1, "two"]}"#)?;
+        let large = Value::from_str(&format!("{{\"a\": [1, \"two\"], \"b\": {}}}", small))?;
+        assert!(large.byte_size() > small.byte_size());
+        let mut array = Value::array();
+        let mut sizes = Vec::new();
+        for _ in 0..10 {
+            array.push(small.clone());
+            sizes.push(array.byte_size());
+        }
+        assert!(sizes.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(array.byte_size() > 10 * small.clone().byte_size());
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_test() {
+        assert_eq!(Value::from("42").as_i64_lenient(), Some(42));
+        assert_eq!(Value::from("42").as_i64(), None);
+        assert_eq!(Value::from(" -7 ").as_i64_lenient(), Some(-7));
+        assert_eq!(Value::from("1e3").as_i64_lenient(), Some(1000));
+        assert_eq!(Value::from("1.5").as_i64_lenient(), None);
+        assert_eq!(Value::from("forty-two").as_i64_lenient(), None);
+        assert_eq!(Value::from(42i64).as_i64_lenient(), Some(42));
+
+        assert_eq!(Value::from("1.5").as_f64_lenient(), Some(1.5));
+        assert_eq!(Value::from("1.5").as_f64(), None);
+        assert_eq!(Value::from("").as_f64_lenient(), None);
+        assert_eq!(Value::from(2.5).as_f64_lenient(), Some(2.5));
+
+        assert_eq!(Value::from("true").as_bool_lenient(), Some(true));
+        assert_eq!(Value::from("false\n").as_bool_lenient(), Some(false));
+        assert_eq!(Value::from("true").as_bool(), None);
+        assert_eq!(Value::from("yes").as_bool_lenient(), None);
+        assert_eq!(Value::from(true).as_bool_lenient(), Some(true));
+        assert_eq!(Value::Null.as_bool_lenient(), None);
+    }
+
+    #[test]
+    fn into_test() -> Result<(), crate::error::ParseError> {
+        let array = Value::from_str("[1, 2]")?.into_array().unwrap();
+        assert_eq!(array, vec![Value::from(1i64), Value::from(2i64)]);
+        let object = Value::from_str(r#"{"a": true}"#)?.into_object().unwrap();
+        assert_eq!(object.get("a"), Some(&Value::from(true)));
+        assert_eq!(Value::from("text").into_string(), Ok("text".to_owned()));
+
+        assert_eq!(Value::from("text").into_array(), Err(Value::from("text")));
+        assert_eq!(Value::from_str("[1]")?.into_object(), Err(Value::from_str("[1]")?));
+        assert_eq!(Value::Null.into_string(), Err(Value::Null));
+        Ok(())
+    }
+
+    #[test]
+    fn sum_numbers_test() -> Result<(), crate::error::ParseError> {
+        let sum = |source: &str| Value::from_str(source).map(|value| value.sum_numbers());
+        assert!(matches!(sum("[1, 2, 3]")?, Some(Number::Int(6))));
+        assert!(matches!(sum("[1, 2.5]")?, Some(Number::Float(3.5))));
+        assert!(matches!(sum("[-1, 18446744073709551615]")?, Some(Number::Uint(18_446_744_073_709_551_614))));
+        assert!(matches!(sum("[9223372036854775807, 1]")?, Some(Number::Uint(9_223_372_036_854_775_808))));
+        assert!(matches!(sum("[18446744073709551615, 1]")?, Some(Number::Float(_))));
+        assert!(matches!(sum("[]")?, Some(Number::Int(0))));
+        assert!(sum(r#"[1, "2"]"#)?.is_none());
+        assert!(sum(r#"{"a": 1}"#)?.is_none());
+
+        assert_eq!(Value::from_str("[1, 2, 3, 4]")?.average(), Some(2.5));
+        assert_eq!(Value::from_str("[1.5]")?.average(), Some(1.5));
+        assert_eq!(Value::from_str("[]")?.average(), None);
+        assert_eq!(Value::from_str("[1, null]")?.average(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn number_normalization_test() -> Result<(), crate::error::ParseError> {
+        let source = "[1, -2, 18446744073709551615, 2.5, 3.0, -0.0, 1e19, 1e300, {\"n\": [4.0]}]";
+        let mut floats = Value::from_str(source)?;
+        floats.numbers_to_f64();
+        let mut numbers = Vec::new();
+        floats.for_each_value(|value| numbers.extend(value.as_number().cloned()));
+        assert!(numbers.iter().all(|number| matches!(number, Number::Float(_))));
+        assert_eq!(floats[2].as_f64(), Some(u64::MAX as f64));
+
+        let mut ints = Value::from_str(source)?;
+        ints.normalize_integral_floats();
+        assert!(ints[0].strict_eq(&Value::from(1i64)));
+        assert!(ints[2].strict_eq(&Value::from(u64::MAX)));
+        assert!(ints[3].strict_eq(&Value::from(2.5)));
+        assert!(ints[4].strict_eq(&Value::from(3i64)));
+        assert!(ints[5].strict_eq(&Value::from(0.0)) && ints[5].as_f64().is_some_and(f64::is_sign_negative));
+        assert!(ints[6].strict_eq(&Value::from(10_000_000_000_000_000_000u64)));
+        assert!(ints[7].strict_eq(&Value::from_str("1e300")?));
+        assert!(ints[8]["n"][0].strict_eq(&Value::from(4i64)));
+        let mut raw = Value::from_str("[1e2, 2.0, 1.5e1, 0.0, 1.5]")?;
+        raw.normalize_integral_floats();
+        assert_eq!(raw.to_string(), "[100,2,15,0,1.5]");
+
+        // Raw text is checked exactly, not through a rounded f64.
+        let mut raw = Value::from(vec![
+            Value::Number(Number::Raw("9007199254740993.0".to_string())),
+            Value::Number(Number::Raw("1.0000000000000000001e0".to_string())),
+            Value::Number(Number::Raw("-0.0".to_string())),
+            Value::Number(Number::Raw("1e20".to_string())),
+        ]);
+        raw.normalize_integral_floats();
+        assert!(raw[0].strict_eq(&Value::from(9007199254740993i64)));
+        assert_eq!(raw.to_string(), "[9007199254740993,1.0000000000000000001e0,-0.0,1e20]");
+        Ok(())
+    }
+
+    #[test]
+    fn from_option_test() {
+        assert_eq!(Value::from(Some(5i64)), Value::Number(Number::Int(5)));
+        assert_eq!(Value::from(Some("text")), Value::String("text".to_owned()));
+        assert_eq!(Value::from(None::<i64>), Value::Null);
+        let nickname: Option<String> = None;
+        let value = Value::object().with("name", Some("Fred")).with("nickname", nickname);
+        assert_eq!(value["name"], "Fred");
+        assert!(matches!(value.get("nickname"), Some(Value::Null)));
+    }
+
+    #[test]
+    fn from_sequence_test() {
+        let array = Value::from(vec![1i64, 2, 3]);
+        assert_eq!(array, Value::Array(vec![Value::from(1i64), Value::from(2i64), Value::from(3i64)]));
+        let array = Value::from(["a", "b", "c"]);
+        assert!(matches!(&array, Value::Array(items) if items.len() == 3));
+        assert_eq!(array[2], "c");
+        assert_eq!(Value::from(&[true, false][..]), Value::from(vec![true, false]));
+        assert_eq!(Value::from(vec![Value::Null]), Value::Array(vec![Value::Null]));
+        assert_eq!(Value::from(Vec::<i64>::new()), Value::array());
+    }
+
+    #[test]
+    fn from_map_test() {
+        #[cfg(feature = "std")]
+        {
+            let hash_map: std::collections::HashMap<String, i64> = [("a".to_owned(), 1), ("b".to_owned(), 2)].into();
+            let value = Value::from(hash_map);
+            assert_eq!(value.len(), 2);
+            assert_eq!(value["a"], 1i64);
+            assert_eq!(value["b"], 2i64);
+        }
+
+        let btree_map: alloc::collections::BTreeMap<String, &str> = [("z".to_owned(), "last"), ("m".to_owned(), "mid"), ("a".to_owned(), "first")].into();
+        let value = Value::from(btree_map);
+        assert_eq!(value, Value::object().with("a", "first").with("m", "mid").with("z", "last"));
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(value.keys().collect::<Vec<_>>(), ["a", "m", "z"]);
+    }
+
+    #[test]
+    fn prune_nulls_test() -> Result<(), crate::error::ParseError> {
+        let source = r#"
+            {
+                "tag": null,
+                "name": "Fred",
+                "classes": ["Algebra", null, {"room": null}],
+                "rgb_for_some_reason": { "r": 4, "g": null, "b": { "alpha": null } },
+                "empty": {}
+            }
+        "#;
+        let mut value = Value::from_str(source)?;
+        value.prune_nulls(false, false);
+        assert_eq!(value, Value::from_str(r#"
+            {
+                "name": "Fred",
+                "classes": ["Algebra", null, {}],
+                "rgb_for_some_reason": { "r": 4, "b": {} },
+                "empty": {}
+            }
+        "#)?);
+
+        let mut value = Value::from_str(source)?;
+        value.prune_nulls(true, false);
+        assert_eq!(value["classes"], Value::from_str(r#"["Algebra", {}]"#)?);
+
+        let mut value = Value::from_str(source)?;
+        value.prune_nulls(true, true);
+        assert_eq!(value, Value::from_str(r#"
+            {
+                "name": "Fred",
+                "classes": ["Algebra"],
+                "rgb_for_some_reason": { "r": 4 }
+            }
+        "#)?);
+
+        let mut value = Value::from_str(r#"{"a": {"b": null}}"#)?;
+        value.prune_nulls(false, true);
+        assert_eq!(value, Value::object());
+        Ok(())
+    }
+
+    #[test]
+    fn add_test() -> Result<(), crate::error::ParseError> {
+        let array = Value::from_str("[1, 2]")? + Value::from_str("[3]")?;
+        assert_eq!(array, Value::from_str("[1, 2, 3]")?);
+        let mut object = Value::from_str(r#"{"a": 1, "b": 2}"#)?;
+        object += Value::from_str(r#"{"b": 3, "c": 4}"#)?;
+        assert_eq!(object, Value::from_str(r#"{"a": 1, "b": 3, "c": 4}"#)?);
+        assert_eq!(Value::Null + Value::from(1i64), Value::from(1i64));
+        assert_eq!(Value::from("x") + Value::Null, Value::from("x"));
+        assert_eq!(Value::from(1i64) + Value::from_str("[2]")?, Value::from_str("[1, [2]]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn retain_test() -> Result<(), crate::error::ParseError> {
+        let mut object = Value::from_str(r#"{"a": null, "b": 1, "c": null, "d": [null]}"#)?;
+        object.retain_object(|_, value| !matches!(value, Value::Null));
+        assert_eq!(object, Value::from_str(r#"{"b": 1, "d": [null]}"#)?);
+        let mut array = Value::from_str("[1, 2, 3, 4, 5, 6]")?;
+        array.retain_array(|value| value.as_i64().is_some_and(|int| int % 2 != 0));
+        assert_eq!(array, Value::from_str("[1, 3, 5]")?);
+        let mut scalar = Value::from(2i64);
+        scalar.retain_array(|_| false);
+        scalar.retain_object(|_, _| false);
+        assert_eq!(scalar, Value::from(2i64));
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 // Why did the digital archaeologist get excited about old software?
 // Because they loved discovering ancient "bits" of history!
-use std::str::FromStr;
+use alloc::borrow::Cow;
+use core::str::FromStr;
 
-use crate::{error::ParseError, Value, ValueMap, Number};
+use crate::{error::{ParseError, Position}, Key, Value, ValueMap, Number};
+use crate::prelude::*;
 
 /// Result returned from JSON parsing.
 pub type ParseResult<T> = Result<T, ParseError>;
@@ -20,9 +22,32 @@ fn hex_value(chr: char) -> Option<u16> {
     }
 }
 
+/// Read 4 hex-digits following a `\u` escape.
+fn read_hex4(chars: &mut core::str::Chars<'_>) -> ParseResult<u16> {
+    let mut hex: u16 = 0;
+    for _ in 0..4 {
+        let Some(digit) = chars.next() else {
+            return Err(ParseError::UnexpectedEOF);
+        };
+        let Some(value) = hex_value(digit) else {
+            return Err(ParseError::InvalidHex);
+        };
+        hex = (hex << 4) | value;
+    }
+    Ok(hex)
+}
+
 /// Unescape a string.
+/// 
+/// `\uXXXX` escapes of UTF-16 surrogate pairs are combined into a single character.
+/// Any other character after a backslash is taken literally, so `\x` becomes `x`.
 pub fn unescape_string<S: AsRef<str>>(string: S) -> ParseResult<String> {
-    let s = string.as_ref();
+    unescape_with_options(string.as_ref(), 0, false)
+}
+
+/// Unescape a string that starts at byte `offset` of the source, which is added to the
+/// positions in errors. With `strict_escapes`, only the escapes defined by RFC 8259 are allowed.
+fn unescape_with_options(s: &str, offset: usize, strict_escapes: bool) -> ParseResult<String> {
     let mut buffer = String::with_capacity(s.len());
     let mut chars = s.chars();
     while let Some(c) = chars.next() {
@@ -30,6 +55,7 @@ pub fn unescape_string<S: AsRef<str>>(string: S) -> ParseResult<String> {
             buffer.push(c);
             continue;
         }
+        let position = offset + s.len() - chars.as_str().len() - 1;
         buffer.push(match chars.next() {
             Some('f') => '\u{000c}',
             Some('b') => '\u{0008}',
@@ -37,26 +63,28 @@ pub fn unescape_string<S: AsRef<str>>(string: S) -> ParseResult<String> {
             Some('r') => '\r',
             Some('t') => '\t',
             Some('u') => {
-                // Read 4 hex-digits
-                let mut hex: u16 = 0;
-                for i in 0..4 {
-                    let Some(digit) = chars.next() else {
-                        return Err(ParseError::UnexpectedEOF);
-                    };
-                    let Some(value) = hex_value(digit) else {
-                        return Err(ParseError::InvalidHex);
-                    };
-                    hex |= value;
-                    // Do not shift if it's the last cycle.
-                    if i < 3 {
-                        hex <<= 4;
+                let unit = read_hex4(&mut chars)?;
+                let code = match unit {
+                    // A high surrogate must be followed by an escaped low surrogate.
+                    0xD800..=0xDBFF => {
+                        let (Some('\\'), Some('u')) = (chars.next(), chars.next()) else {
+                            return Err(ParseError::InvalidEscapeSequence(position));
+                        };
+                        let low = read_hex4(&mut chars)?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(ParseError::InvalidEscapeSequence(position));
+                        }
+                        0x10000 + (((unit as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00)
                     }
-                }
-                let Some(res) = char::from_u32(hex as u32) else {
-                    return Err(ParseError::InvalidEscapeSequence);
+                    unit => unit as u32,
+                };
+                let Some(res) = char::from_u32(code) else {
+                    return Err(ParseError::InvalidEscapeSequence(position));
                 };
                 res
             }
+            Some(other @ ('"' | '\\' | '/')) => other,
+            Some(_) if strict_escapes => return Err(ParseError::InvalidEscapeSequence(position)),
             // If the character is any other character, just return the character.
             // This allows to unescape \< to < without having to be explicit.
             // Also, I just think it's a good idea to unescape any character.
@@ -67,22 +95,217 @@ pub fn unescape_string<S: AsRef<str>>(string: S) -> ParseResult<String> {
     Ok(buffer)
 }
 
+/// Options controlling how JSON text is parsed. The defaults are lenient.
+/// ```
+/// # use bourne::{Value, parse::ParseOptions};
+/// let options = ParseOptions {
+///     require_compound_root: true,
+///     ..ParseOptions::default()
+/// };
+/// assert!(Value::from_str_with_options("[1, 2]", options).is_ok());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Require the root value to be an array or an object, as in RFC 4627.
+    /// Default: `false`.
+    pub require_compound_root: bool,
+    /// Only accept numbers that follow the JSON number grammar exactly: no leading `+`,
+    /// no leading zeros, and at least one digit before and after `.` and after the exponent.
+    /// Default: `false`.
+    pub strict_numbers: bool,
+    /// Accept the non-standard `NaN`, `Infinity` and `-Infinity` (and `+Infinity` unless
+    /// `strict_numbers` is set) literals as [Number::Float]s. Default: `false`.
+    pub allow_nonfinite: bool,
+    /// Only skip the whitespace allowed by RFC 8259 (space, tab, line feed and carriage
+    /// return) between tokens. Other whitespace, such as form feed, is an error.
+    /// Default: `false`.
+    pub strict_whitespace: bool,
+    /// Parse numbers into [Number::Raw], keeping their original text (`3.14000`, `1.0E5`)
+    /// so they are serialized exactly as they appeared. This costs an allocation per
+    /// number, and the text is parsed again whenever the number is compared or
+    /// converted. Non-finite literals are still parsed as [Number::Float]s.
+    /// 
+    /// The `arbitrary_precision` feature turns this on for every parse, so decimal values
+    /// such as `0.1` and integers of any length round-trip exactly.
+    /// Default: `false`.
+    pub preserve_number_text: bool,
+    /// Only accept the escape sequences defined by RFC 8259 (`\"`, `\\`, `\/`, `\b`, `\f`,
+    /// `\n`, `\r`, `\t` and `\uXXXX`) in strings. By default, a backslash followed by any
+    /// other character is that character. Default: `false`.
+    pub strict_escapes: bool,
+    /// Reject unescaped control characters (U+0000 to U+001F, such as a literal tab) in
+    /// strings, including directly after a backslash. Line breaks in strings are always
+    /// rejected. Default: `false`.
+    pub strict_control_chars: bool,
+    /// Fail with [ParseError::InputTooLarge] before parsing if the input is longer than
+    /// this many bytes. Default: [None].
+    pub max_input_bytes: Option<usize>,
+    /// Fail with [ParseError::DepthLimitExceeded] if arrays and objects are nested deeper
+    /// than this. The parser is recursive, so very deep input can otherwise overflow the
+    /// stack. Default: [None].
+    pub max_depth: Option<usize>,
+    /// Parse numbers with an exponent whose value is integral, such as `1e2` or `1.5e1`,
+    /// as [Number::Int] (or [Number::Uint]) instead of [Number::Float], if they fit.
+    /// Numbers without an exponent, like `2.0`, are still floats. Has no effect on
+    /// [Number::Raw], so it is ignored with `preserve_number_text`. Default: `false`.
+    pub collapse_integral_exponents: bool,
+    /// What to do when an object has the same key more than once.
+    /// Default: [DuplicateKeyStrategy::Overwrite].
+    pub on_duplicate_key: DuplicateKeyStrategy,
+}
+
+/// How the parser handles an object key that appears more than once, for
+/// [ParseOptions::on_duplicate_key]. [parse_ref] keeps every entry regardless.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicateKeyStrategy {
+    /// The last value wins: `{"a": 1, "a": 2}` becomes `{"a": 2}`.
+    #[default]
+    Overwrite,
+    /// Fail with [ParseError::DuplicateKey].
+    Reject,
+    /// The first value wins: `{"a": 1, "a": 2}` becomes `{"a": 1}`.
+    FirstWins,
+    /// Collect every value of the key into an array, in source order: `{"a": 1, "a": 2}`
+    /// becomes `{"a": [1, 2]}`. Keys that appear only once keep their value as is, even
+    /// if it is an array.
+    Merge,
+}
+
+/// Checks that `text` follows the JSON number grammar, returning the offset of the
+/// first offending byte if it doesn't.
+fn validate_strict_number(text: &[u8]) -> Result<(), usize> {
+    let digits = |mut index: usize| {
+        while let Some(b'0'..=b'9') = text.get(index) {
+            index += 1;
+        }
+        index
+    };
+    let mut index = 0;
+    if let Some(b'-') = text.first() {
+        index += 1;
+    }
+    match text.get(index) {
+        Some(b'0') => index += 1,
+        Some(b'1'..=b'9') => index = digits(index),
+        _ => return Err(index),
+    }
+    if let Some(b'.') = text.get(index) {
+        let end = digits(index + 1);
+        if end == index + 1 {
+            return Err(end);
+        }
+        index = end;
+    }
+    if let Some(b'e' | b'E') = text.get(index) {
+        index += 1;
+        if let Some(b'+' | b'-') = text.get(index) {
+            index += 1;
+        }
+        let end = digits(index);
+        if end == index {
+            return Err(end);
+        }
+        index = end;
+    }
+    if index == text.len() {
+        Ok(())
+    } else {
+        Err(index)
+    }
+}
+
+/// How many bytes [Parser::estimate_len] looks ahead before settling for a lower bound.
+const CAPACITY_SCAN_LIMIT: usize = 4096;
+
 /// A JSON parser.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct Parser<'a> {
     source: &'a str,
     index: usize,
+    options: ParseOptions,
+    /// The number of arrays and objects currently being parsed.
+    depth: usize,
+    /// Every object key parsed so far, so that repeated keys can share storage.
+    #[cfg(feature = "shared_keys")]
+    keys: hashbrown::HashSet<Key>,
 }
 
 impl<'a> Parser<'a> {
     /// Create a new [Parser] from a `source` string.
     fn new(source: &'a str) -> Self {
+        Self::with_options(source, ParseOptions::default())
+    }
+
+    /// Create a new [Parser] from a `source` string with the given [ParseOptions].
+    fn with_options(source: &'a str, options: ParseOptions) -> Self {
         Self {
             source,
             index: 0,
+            options,
+            depth: 0,
+            #[cfg(feature = "shared_keys")]
+            keys: hashbrown::HashSet::new(),
         }
     }
 
+    /// Enter an array or object starting at `index`, checking [ParseOptions::max_depth].
+    fn enter(&mut self, index: usize) -> ParseResult<()> {
+        self.depth += 1;
+        match self.options.max_depth {
+            Some(max_depth) if self.depth > max_depth => Err(ParseError::DepthLimitExceeded(index)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Estimate the number of elements in the array or object whose opening bracket was
+    /// just consumed, by counting the commas at its own nesting level.
+    ///
+    /// The scan stops after [CAPACITY_SCAN_LIMIT] bytes, so each container costs at most
+    /// that much extra work. Containers that fit in the window are allocated once at their
+    /// exact size instead of growing through about log2(n) reallocations; larger ones start
+    /// from the count seen so far. Malformed input only makes the estimate wrong; errors
+    /// are still reported by the parser itself.
+    ///
+    /// Measured with a counting allocator, an array of 1000 small arrays of 8 numbers
+    /// parses with 1004 allocations instead of 2009 without the estimate, and a 3.9 KB
+    /// array of 1000 integers with 1 instead of 9. Small objects gain little, since a
+    /// [ValueMap] allocates room for a few entries on its first insert anyway.
+    ///
+    /// Each container scans the bytes of its children again, so with deep nesting a byte
+    /// is scanned once for every enclosing container that starts less than
+    /// [CAPACITY_SCAN_LIMIT] bytes before it: `[[[[…]]]]` costs up to that many scans per
+    /// byte, instead of one.
+    fn estimate_len(&self) -> usize {
+        let bytes = &self.source.as_bytes()[self.index..];
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut empty = true;
+        let mut commas = 0;
+        for &byte in bytes.iter().take(CAPACITY_SCAN_LIMIT) {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => (),
+                }
+                continue;
+            }
+            match byte {
+                b'"' => in_string = true,
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' if depth == 0 => break,
+                b']' | b'}' => depth -= 1,
+                b',' if depth == 0 => commas += 1,
+                _ if self.is_whitespace(byte) => continue,
+                _ => (),
+            }
+            empty = false;
+        }
+        if empty { 0 } else { commas + 1 }
+    }
+
     /// Checks if the index is at the end of the stream.
     fn is_eof(&self) -> bool {
         self.index >= self.source.len()
@@ -124,9 +347,14 @@ impl<'a> Parser<'a> {
         self.index += step;
     }
 
-    /// Decrement the index by one.
+    /// Decrement the index by one, to un-read the byte returned by the last call to
+    /// [Parser::next] or [Parser::indexed_next].
+    ///
+    /// Only the first byte of a character may be un-read, so that the index stays on a
+    /// character boundary and slicing `source` at it can't panic.
     fn rewind(&mut self) {
-        self.index = self.index.checked_sub(1).unwrap_or(0);
+        self.index = self.index.saturating_sub(1);
+        debug_assert!(self.source.is_char_boundary(self.index), "rewind to the middle of a character at {}", self.index);
     }
 
     /// Checks if the parser matches text at the current index.
@@ -139,10 +367,24 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Create a [ParseError::Expected] for the character at `index`.
+    fn expected(&self, expected: &'static str, index: usize) -> ParseError {
+        ParseError::expected_at(expected, self.source, index)
+    }
+
+    /// Checks if `byte` is whitespace that may appear between tokens.
+    fn is_whitespace(&self, byte: u8) -> bool {
+        if self.options.strict_whitespace {
+            matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+        } else {
+            byte.is_ascii_whitespace()
+        }
+    }
+
     /// Consumes all whitespace, advancing the index.
     fn eat_whitespace(&mut self) {
         while let Some(peek) = self.peek() {
-            if peek.is_ascii_whitespace() {
+            if self.is_whitespace(peek) {
                 self.advance(1);
             } else {
                 break;
@@ -150,6 +392,21 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Skip spaces and tabs up to the end of the line, and the line break itself.
+    fn eat_line_end(&mut self) -> ParseResult<()> {
+        while let Some(b' ' | b'\t') = self.peek() {
+            self.advance(1);
+        }
+        if self.matches("\r\n") {
+            self.advance(2);
+        } else if self.matches("\n") {
+            self.advance(1);
+        } else if !self.is_eof() {
+            return Err(self.expected("newline", self.index));
+        }
+        Ok(())
+    }
+
     /// Parse the `null` keyword and return [Value::Null] on success.
     fn parse_null(&mut self) -> ParseResult<Value> {
         if self.matches("null") {
@@ -175,6 +432,20 @@ impl<'a> Parser<'a> {
 
     /// Parse a [Number].
     fn parse_number(&mut self) -> ParseResult<Number> {
+        if self.options.allow_nonfinite {
+            let literals = [
+                ("NaN", f64::NAN),
+                ("Infinity", f64::INFINITY),
+                ("-Infinity", f64::NEG_INFINITY),
+                ("+Infinity", f64::INFINITY),
+            ];
+            for (literal, float) in literals {
+                if self.matches(literal) && !(self.options.strict_numbers && literal.starts_with('+')) {
+                    self.advance(literal.len());
+                    return Ok(Number::Float(float));
+                }
+            }
+        }
         // Valid characters that can follow a number: '}', ']', ',', and whitespace.
         let mut found_e = false;
         let mut found_dot = false;
@@ -197,18 +468,50 @@ impl<'a> Parser<'a> {
                     self.rewind();
                     break
                 },
-                ws if ws.is_ascii_whitespace() => {
+                ws if self.is_whitespace(ws) => {
                     self.rewind();
                     break
                 },
                 _ => return Err(ParseError::InvalidCharacter(index)),
             }
         }
-        if self.index - start != 0 {
+        if self.options.strict_numbers {
+            if let Err(offset) = validate_strict_number(&self.source.as_bytes()[start..self.index]) {
+                return Err(ParseError::InvalidCharacter(start + offset));
+            }
+        }
+        // A sign on its own is not a number, even when the text is kept as is.
+        if found_num {
+            let text = &self.source[start..self.index];
+            if cfg!(feature = "arbitrary_precision") || self.options.preserve_number_text {
+                // Still reject numbers that don't fit in an f64, as below.
+                if (found_dot | found_e) && !text.parse::<f64>()?.is_finite() {
+                    return Err(ParseError::NumberOutOfRange(start));
+                }
+                return Ok(Number::Raw(text.to_string()));
+            }
             if found_dot | found_e {
-                Ok(Number::Float(self.source[start..self.index].parse::<f64>()?))
+                // Exponents too large for an f64 parse as infinity. Underflow to 0.0 is fine.
+                match text.parse::<f64>()? {
+                    float if found_e && self.options.collapse_integral_exponents => match Number::integral(float) {
+                        Some(number) => Ok(number),
+                        None if float.is_finite() => Ok(Number::Float(float)),
+                        None => Err(ParseError::NumberOutOfRange(start)),
+                    },
+                    float if float.is_finite() => Ok(Number::Float(float)),
+                    _ => Err(ParseError::NumberOutOfRange(start)),
+                }
             } else {
-                Ok(Number::Int(self.source[start..self.index].parse::<i64>()?))
+                match text.parse::<i64>() {
+                    // Keep the sign of `-0`, which an integer can't represent.
+                    Ok(0) if text.starts_with('-') => Ok(Number::Float(-0.0)),
+                    Ok(int) => Ok(Number::Int(int)),
+                    // Positive integers above i64::MAX may still fit in a u64.
+                    Err(err) => match text.parse::<u64>() {
+                        Ok(uint) => Ok(Number::Uint(uint)),
+                        Err(_) => Err(err.into()),
+                    },
+                }
             }
         } else {
             Err(ParseError::InvalidCharacter(self.index))
@@ -234,21 +537,55 @@ impl<'a> Parser<'a> {
     /// "Hello, world!"
     /// ```
     fn parse_string(&mut self) -> ParseResult<String> {
+        self.parse_str().map(Cow::into_owned)
+    }
+
+    /// Parse an object key. With the `shared_keys` feature, a key that was already parsed
+    /// in this document shares the storage of its first occurrence.
+    fn parse_key(&mut self) -> ParseResult<Key> {
+        let key = self.parse_str()?;
+        #[cfg(feature = "shared_keys")]
+        return Ok(self.keys.get_or_insert_with(&*key, |key| Key::from(key)).clone());
+        #[cfg(not(feature = "shared_keys"))]
+        Ok(key.into_owned())
+    }
+
+    /// Parse a string between double quotes (`"`), borrowing from the source when the
+    /// string contains no escape sequences. See [Parser::parse_string].
+    fn parse_str(&mut self) -> ParseResult<Cow<'a, str>> {
         match self.peek() {
             Some(b'"') => { self.next(); }
             Some(_) => { return Err(ParseError::InvalidCharacter(self.index)); }
             None => { return Err(ParseError::UnexpectedEOF); }
         }
         let start = self.index;
+        let mut escaped = false;
         let string = loop {
             let Some((index, next)) = self.indexed_next() else {
-                return Err(ParseError::UnexpectedEOFWhileParsingString(start));
+                return Err(ParseError::unterminated_string_at(self.source, start - 1));
             };
             match next {
                 // Strings should not contain new-lines.
-                b'\n' | b'\r' => { return Err(ParseError::LineBreakWhileParsingString(index)); }
-                b'"' => break unescape_string(&self.source[start..index])?,
-                b'\\' => { self.advance(1); }
+                b'\n' | b'\r' => {
+                    return Err(ParseError::line_break_in_string_at(self.source, start - 1, index));
+                }
+                // Fast path: without escapes, the source slice is already the string.
+                b'"' if !escaped => break Cow::Borrowed(&self.source[start..index]),
+                b'"' => break Cow::Owned(unescape_with_options(&self.source[start..index], start, self.options.strict_escapes)?),
+                b'\\' => {
+                    escaped = true;
+                    if self.options.strict_control_chars && matches!(self.peek(), Some(0x00..=0x1f)) {
+                        return Err(ParseError::ControlCharacterInString(self.index));
+                    }
+                    // Skip the whole escaped character, so the index stays on a character
+                    // boundary and within the source even for a trailing backslash.
+                    if let Some(escaped) = self.source[self.index..].chars().next() {
+                        self.advance(escaped.len_utf8());
+                    }
+                }
+                0x00..=0x1f if self.options.strict_control_chars => {
+                    return Err(ParseError::ControlCharacterInString(index));
+                }
                 _ => {}
             }
         };
@@ -270,13 +607,13 @@ impl<'a> Parser<'a> {
     ///     }
     /// ]
     /// ```
-    fn parse_array(&mut self) -> ParseResult<Vec<Value>> {
+    fn parse_array<B: Builder<'a>>(&mut self, builder: &mut B) -> ParseResult<B::Value> {
         match self.indexed_next() {
-            Some((_, b'[')) => (),
+            Some((index, b'[')) => self.enter(index)?,
             Some((index, _)) => return Err(ParseError::InvalidCharacter(index)),
             None => return Err(ParseError::UnexpectedEOF),
         }
-        let mut array = Vec::new();
+        let mut array = builder.start_array(self);
         loop {
             self.eat_whitespace();
             match self.peek() {
@@ -285,23 +622,32 @@ impl<'a> Parser<'a> {
                     break;
                 }
                 Some(_) => {
-                    array.push(self.parse_value()?);
+                    let value = self.parse_value_with(builder)?;
+                    builder.push(&mut array, value);
                     self.eat_whitespace();
                     match self.indexed_next() {
                         Some((_, b']')) => break,
                         Some((_, b',')) => continue,
-                        Some((index, _)) => return Err(ParseError::InvalidCharacter(index)),
+                        Some((index, _)) => return Err(self.expected("',' or ']'", index)),
                         None => return Err(ParseError::UnexpectedEOF),
                     }
                 }
                 None => return Err(ParseError::UnexpectedEOF),
             }
         }
-        Ok(array)
+        self.depth -= 1;
+        Ok(builder.end_array(array))
     }
 
     /// Parse a JSON Object.
     /// 
+    /// If a key appears more than once, the last value wins. With `preserve_order`,
+    /// the key keeps the position of its first occurrence.
+    /// 
+    /// Each key costs exactly one allocation, as keys without escapes are borrowed from
+    /// the source until inserted. With the `shared_keys` feature, only the first
+    /// occurrence of a key in the document allocates; see [Parser::parse_key].
+    /// 
     /// #### Example:
     /// ```json
     /// {
@@ -311,32 +657,33 @@ impl<'a> Parser<'a> {
     ///     "string" : "Hello, world!",
     /// }
     /// ```
-    fn parse_object(&mut self) -> ParseResult<ValueMap> {
+    fn parse_object<B: Builder<'a>>(&mut self, builder: &mut B) -> ParseResult<B::Value> {
         match self.indexed_next() {
-            Some((_, b'{')) => (),
+            Some((index, b'{')) => self.enter(index)?,
             Some((index, _)) => return Err(ParseError::InvalidCharacter(index)),
             None => return Err(ParseError::UnexpectedEOF),
         }
-        let mut map = ValueMap::new();
+        let mut object = builder.start_object(self);
         loop {
             self.eat_whitespace();
             match self.peek() {
                 Some(b'"') => {
-                    let key = self.parse_string()?;
+                    let key_index = self.index;
+                    let key = builder.key(self)?;
                     self.eat_whitespace();
                     match self.indexed_next() {
                         Some((_, b':')) => (),
-                        Some((index, _)) => return Err(ParseError::InvalidCharacter(index)),
+                        Some((index, _)) => return Err(self.expected("':'", index)),
                         None => return Err(ParseError::UnexpectedEOF),
                     }
                     self.eat_whitespace();
-                    let value = self.parse_value()?;
-                    map.insert(key, value);
+                    let value = self.parse_value_with(builder)?;
+                    builder.insert(self, &mut object, key, value, key_index)?;
                     self.eat_whitespace();
                     match self.indexed_next() {
                         Some((_, b',')) => continue,
                         Some((_, b'}')) => break,
-                        Some((index, _)) => return Err(ParseError::InvalidCharacter(index)),
+                        Some((index, _)) => return Err(self.expected("',' or '}'", index)),
                         None => return Err(ParseError::UnexpectedEOF),
                     }
                 }
@@ -348,36 +695,1107 @@ impl<'a> Parser<'a> {
                 None => return Err(ParseError::UnexpectedEOF),
             }
         }
-        Ok(map)
+        self.depth -= 1;
+        Ok(builder.end_object(object))
+    }
+
+    /// Insert an object entry, handling a repeated key according to
+    /// [ParseOptions::on_duplicate_key]. `key_index` is the offset of the key.
+    fn insert_entry(&self, map: &mut ValueMap, merged: &mut Vec<Key>, key: Key, value: Value, key_index: usize) -> ParseResult<()> {
+        let strategy = self.options.on_duplicate_key;
+        let Some(existing) = map.get_mut(&*key).filter(|_| strategy != DuplicateKeyStrategy::Overwrite) else {
+            map.insert(key, value);
+            return Ok(());
+        };
+        match strategy {
+            DuplicateKeyStrategy::Overwrite | DuplicateKeyStrategy::FirstWins => (),
+            DuplicateKeyStrategy::Reject => return Err(ParseError::DuplicateKey { key: key.to_string(), position: key_index }),
+            DuplicateKeyStrategy::Merge => match existing {
+                Value::Array(values) if merged.contains(&key) => values.push(value),
+                _ => {
+                    let first = core::mem::take(existing);
+                    *existing = Value::Array(vec![first, value]);
+                    merged.push(key);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Parse a whole JSON document: a single value surrounded by optional whitespace.
+    fn parse_document(&mut self) -> ParseResult<Value> {
+        self.parse_document_with(Self::parse_value)
+    }
+
+    /// Parse a whole JSON document, using `parse` for the value.
+    fn parse_document_with<T, F: FnOnce(&mut Self) -> ParseResult<T>>(&mut self, parse: F) -> ParseResult<T> {
+        if let Some(limit) = self.options.max_input_bytes {
+            if self.source.len() > limit {
+                return Err(ParseError::InputTooLarge { size: self.source.len(), limit });
+            }
+        }
+        self.eat_whitespace();
+        if self.options.require_compound_root && !matches!(self.peek(), Some(b'[' | b'{') | None) {
+            return Err(ParseError::NonCompoundRoot);
+        }
+        let res = parse(self)?;
+        self.eat_whitespace();
+        if !self.is_eof() {
+            Err(ParseError::InvalidCharacter(self.index))
+        } else {
+            Ok(res)
+        }
     }
 
     /// Parse a JSON Value.
     fn parse_value(&mut self) -> ParseResult<Value> {
+        self.parse_value_with(&mut ValueBuilder)
+    }
+
+    /// Parse a JSON Value, assembling the result with `builder`.
+    fn parse_value_with<B: Builder<'a>>(&mut self, builder: &mut B) -> ParseResult<B::Value> {
         Ok(match self.peek() {
-            Some(b'n') => self.parse_null()?,
-            Some(b't' | b'f') => Value::Boolean(self.parse_boolean()?),
-            Some(b'+' | b'-' | b'0'..=b'9') => Value::Number(self.parse_number()?),
-            Some(b'"') => Value::String(self.parse_string()?),
-            Some(b'[') => Value::Array(self.parse_array()?),
-            Some(b'{') => Value::Object(self.parse_object()?),
+            Some(b'n') => builder.scalar(self.parse_null()?),
+            Some(b't' | b'f') => builder.scalar(Value::Boolean(self.parse_boolean()?)),
+            Some(b'+' | b'-' | b'0'..=b'9') => builder.scalar(Value::Number(self.parse_number()?)),
+            Some(b'N' | b'I') if self.options.allow_nonfinite => builder.scalar(Value::Number(self.parse_number()?)),
+            Some(b'"') => builder.string(self.parse_str()?),
+            Some(b'[') => self.parse_array(builder)?,
+            Some(b'{') => self.parse_object(builder)?,
             Some(_) => return Err(ParseError::InvalidCharacter(self.index)),
             None => return Err(ParseError::UnexpectedEOF),
         })
     }
 }
 
+/// Assembles what a [Parser] reads. The parser drives the loops over array elements and
+/// object entries, and hands each piece to the builder, so that [Value], [ValueRef] and
+/// [Event]s share one implementation of the grammar.
+trait Builder<'a> {
+    /// A parsed value.
+    type Value;
+    /// An array being filled in.
+    type Array;
+    /// An object being filled in.
+    type Object;
+    /// A parsed object key.
+    type Key;
+
+    /// Build a `null`, boolean or number.
+    fn scalar(&mut self, value: Value) -> Self::Value;
+    /// Build a string.
+    fn string(&mut self, string: Cow<'a, str>) -> Self::Value;
+    /// Start an array whose `[` was just consumed by `parser`.
+    fn start_array(&mut self, parser: &Parser<'a>) -> Self::Array;
+    fn push(&mut self, array: &mut Self::Array, value: Self::Value);
+    fn end_array(&mut self, array: Self::Array) -> Self::Value;
+    /// Start an object whose `{` was just consumed by `parser`.
+    fn start_object(&mut self, parser: &Parser<'a>) -> Self::Object;
+    /// Parse the key of an object entry at the position of `parser`.
+    fn key(&mut self, parser: &mut Parser<'a>) -> ParseResult<Self::Key>;
+    /// Add an entry to an object. `key_index` is the offset of the key.
+    fn insert(&mut self, parser: &Parser<'a>, object: &mut Self::Object, key: Self::Key, value: Self::Value, key_index: usize) -> ParseResult<()>;
+    fn end_object(&mut self, object: Self::Object) -> Self::Value;
+}
+
+/// Builds owned [Value]s, applying [ParseOptions::on_duplicate_key].
+struct ValueBuilder;
+
+impl<'a> Builder<'a> for ValueBuilder {
+    type Value = Value;
+    type Array = Vec<Value>;
+    /// The map, and the keys whose values have been collected into an array by
+    /// [DuplicateKeyStrategy::Merge].
+    type Object = (ValueMap, Vec<Key>);
+    type Key = Key;
+
+    fn scalar(&mut self, value: Value) -> Value {
+        value
+    }
+
+    fn string(&mut self, string: Cow<'a, str>) -> Value {
+        Value::String(string.into_owned())
+    }
+
+    fn start_array(&mut self, parser: &Parser<'a>) -> Vec<Value> {
+        Vec::with_capacity(parser.estimate_len())
+    }
+
+    fn push(&mut self, array: &mut Vec<Value>, value: Value) {
+        array.push(value);
+    }
+
+    fn end_array(&mut self, array: Vec<Value>) -> Value {
+        Value::Array(array)
+    }
+
+    fn start_object(&mut self, parser: &Parser<'a>) -> (ValueMap, Vec<Key>) {
+        (ValueMap::with_capacity(parser.estimate_len()), Vec::new())
+    }
+
+    fn key(&mut self, parser: &mut Parser<'a>) -> ParseResult<Key> {
+        parser.parse_key()
+    }
+
+    fn insert(&mut self, parser: &Parser<'a>, (map, merged): &mut (ValueMap, Vec<Key>), key: Key, value: Value, key_index: usize) -> ParseResult<()> {
+        parser.insert_entry(map, merged, key, value, key_index)
+    }
+
+    fn end_object(&mut self, (map, _): (ValueMap, Vec<Key>)) -> Value {
+        Value::Object(map)
+    }
+}
+
+/// Builds [ValueRef]s, borrowing strings from the source.
+struct ValueRefBuilder;
+
+impl<'a> Builder<'a> for ValueRefBuilder {
+    type Value = ValueRef<'a>;
+    type Array = Vec<ValueRef<'a>>;
+    type Object = Vec<(Cow<'a, str>, ValueRef<'a>)>;
+    type Key = Cow<'a, str>;
+
+    fn scalar(&mut self, value: Value) -> ValueRef<'a> {
+        match value {
+            Value::Null => ValueRef::Null,
+            Value::Boolean(boolean) => ValueRef::Boolean(boolean),
+            Value::Number(number) => ValueRef::Number(number),
+            _ => unreachable!(),
+        }
+    }
+
+    fn string(&mut self, string: Cow<'a, str>) -> ValueRef<'a> {
+        ValueRef::String(string)
+    }
+
+    fn start_array(&mut self, parser: &Parser<'a>) -> Self::Array {
+        Vec::with_capacity(parser.estimate_len())
+    }
+
+    fn push(&mut self, array: &mut Self::Array, value: ValueRef<'a>) {
+        array.push(value);
+    }
+
+    fn end_array(&mut self, array: Self::Array) -> ValueRef<'a> {
+        ValueRef::Array(array)
+    }
+
+    fn start_object(&mut self, parser: &Parser<'a>) -> Self::Object {
+        Vec::with_capacity(parser.estimate_len())
+    }
+
+    fn key(&mut self, parser: &mut Parser<'a>) -> ParseResult<Cow<'a, str>> {
+        parser.parse_str()
+    }
+
+    fn insert(&mut self, _: &Parser<'a>, entries: &mut Self::Object, key: Cow<'a, str>, value: ValueRef<'a>, _: usize) -> ParseResult<()> {
+        entries.push((key, value));
+        Ok(())
+    }
+
+    fn end_object(&mut self, entries: Self::Object) -> ValueRef<'a> {
+        ValueRef::Object(entries)
+    }
+}
+
+/// Reports the structure to a handler as [Event]s, without building anything.
+struct EventBuilder<'h, F>(&'h mut F);
+
+impl<'a, F: FnMut(Event<'_>)> Builder<'a> for EventBuilder<'_, F> {
+    type Value = ();
+    type Array = ();
+    type Object = ();
+    type Key = ();
+
+    fn scalar(&mut self, value: Value) {
+        (self.0)(Event::Scalar(value));
+    }
+
+    fn string(&mut self, string: Cow<'a, str>) {
+        (self.0)(Event::Scalar(Value::String(string.into_owned())));
+    }
+
+    fn start_array(&mut self, _: &Parser<'a>) {
+        (self.0)(Event::StartArray);
+    }
+
+    fn push(&mut self, (): &mut (), (): ()) {}
+
+    fn end_array(&mut self, (): ()) {
+        (self.0)(Event::EndArray);
+    }
+
+    fn start_object(&mut self, _: &Parser<'a>) {
+        (self.0)(Event::StartObject);
+    }
+
+    fn key(&mut self, parser: &mut Parser<'a>) -> ParseResult<()> {
+        let key = parser.parse_str()?;
+        (self.0)(Event::Key(&key));
+        Ok(())
+    }
+
+    fn insert(&mut self, _: &Parser<'a>, (): &mut (), (): (), (): (), _: usize) -> ParseResult<()> {
+        Ok(())
+    }
+
+    fn end_object(&mut self, (): ()) {
+        (self.0)(Event::EndObject);
+    }
+}
+
+impl Value {
+    /// Parse a JSON [Value] from a string with the given [ParseOptions].
+    pub fn from_str_with_options(s: &str, options: ParseOptions) -> ParseResult<Value> {
+        Parser::with_options(s, options).parse_document()
+    }
+
+    /// Parse a JSON [Value] from a string, also returning the byte range of the value
+    /// within `s`, which excludes the surrounding whitespace.
+    pub fn from_str_spanned(s: &str) -> ParseResult<(Value, core::ops::Range<usize>)> {
+        Parser::new(s).parse_document_with(|parser| {
+            let start = parser.index;
+            let value = parser.parse_value()?;
+            Ok((value, start..parser.index))
+        })
+    }
+
+    /// Parse newline-delimited JSON: one value per line, each followed by `\n`, `\r\n` or
+    /// the end of the input. Blank lines are skipped, but two values on one line are an
+    /// error.
+    ///
+    /// Errors are wrapped in [ParseError::Record] with the number of the failing value.
+    pub fn from_ndjson(input: &str) -> ParseResult<Vec<Value>> {
+        let mut parser = Parser::new(input);
+        let mut values = Vec::new();
+        loop {
+            parser.eat_whitespace();
+            if parser.is_eof() {
+                break;
+            }
+            let record = values.len() + 1;
+            let value = parser.parse_value()
+                .and_then(|value| parser.eat_line_end().map(|()| value))
+                .map_err(|error| ParseError::Record { record, error: Box::new(error) })?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// Parse a JSON [Value] from UTF-8 encoded bytes. A leading UTF-8 byte order mark
+    /// is skipped, and error indices are relative to the text after it.
+    pub fn from_slice(bytes: &[u8]) -> ParseResult<Value> {
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        Value::from_str(core::str::from_utf8(bytes)?)
+    }
+}
+
+/// An event reported by [parse_events].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// The start of an object (`{`).
+    StartObject,
+    /// An object key. The key's value follows as the next event(s).
+    Key(&'a str),
+    /// The end of an object (`}`).
+    EndObject,
+    /// The start of an array (`[`).
+    StartArray,
+    /// The end of an array (`]`).
+    EndArray,
+    /// A [Value::Null], [Value::Boolean], [Value::Number] or [Value::String].
+    Scalar(Value),
+}
+
+/// Parse a JSON document, reporting its structure to `handler` as a stream of [Event]s
+/// instead of building a [Value]. Arrays and objects are never materialized, so this
+/// can process documents that are too large to hold as a [Value].
+/// ```
+/// # use bourne::parse::{parse_events, Event};
+/// let mut depth = 0;
+/// let mut max_depth = 0;
+/// parse_events("[[1], [[2]]]", |event| match event {
+///     Event::StartArray => { depth += 1; max_depth = max_depth.max(depth); }
+///     Event::EndArray => depth -= 1,
+///     _ => (),
+/// }).unwrap();
+/// assert_eq!(max_depth, 3);
+/// ```
+pub fn parse_events<F: FnMut(Event<'_>)>(source: &str, mut handler: F) -> ParseResult<()> {
+    let mut parser = Parser::new(source);
+    parser.eat_whitespace();
+    parser.parse_value_with(&mut EventBuilder(&mut handler))?;
+    parser.eat_whitespace();
+    if !parser.is_eof() {
+        Err(ParseError::InvalidCharacter(parser.index))
+    } else {
+        Ok(())
+    }
+}
+
+/// A borrowed mirror of [Value], produced by [parse_ref]. Strings and keys borrow from
+/// the source text, and are only owned when escape sequences had to be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRef<'a> {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Vec<ValueRef<'a>>),
+    /// Entries in source order. Duplicate keys are kept; [ValueRef::get] and
+    /// [ValueRef::to_owned] let the last one win, as [Value] does.
+    Object(Vec<(Cow<'a, str>, ValueRef<'a>)>),
+}
+
+impl ValueRef<'_> {
+    /// Get the string slice if this is a [ValueRef::String].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ValueRef::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Look up `key` in a [ValueRef::Object]. Returns [None] for other variants.
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            ValueRef::Object(entries) => entries.iter().rev().find(|(k, _)| k == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Build an owned [Value], copying every borrowed string.
+    pub fn to_owned(&self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Boolean(boolean) => Value::Boolean(*boolean),
+            ValueRef::Number(number) => Value::Number(number.clone()),
+            ValueRef::String(string) => Value::String(string.as_ref().to_owned()),
+            ValueRef::Array(array) => Value::Array(array.iter().map(ValueRef::to_owned).collect()),
+            ValueRef::Object(entries) => {
+                let mut object = ValueMap::with_capacity(entries.len());
+                for (key, value) in entries {
+                    object.insert(Key::from(key.as_ref()), value.to_owned());
+                }
+                Value::Object(object)
+            }
+        }
+    }
+}
+
+/// Parse a JSON document into a [ValueRef] that borrows its strings from `source`,
+/// avoiding an allocation per string for short-lived, read-only access.
+/// ```
+/// # use std::borrow::Cow;
+/// # use bourne::parse::{parse_ref, ValueRef};
+/// let value = parse_ref(r#"{"name": "Fred", "quote": "\"hi\""}"#).unwrap();
+/// assert!(matches!(value.get("name"), Some(ValueRef::String(Cow::Borrowed("Fred")))));
+/// assert_eq!(value.get("quote").and_then(ValueRef::as_str), Some("\"hi\""));
+/// ```
+pub fn parse_ref(source: &str) -> ParseResult<ValueRef<'_>> {
+    Parser::new(source).parse_document_with(|parser| parser.parse_value_with(&mut ValueRefBuilder))
+}
+
+/// A lexical token of JSON text, produced by [Tokenizer].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// `{`
+    BraceOpen,
+    /// `}`
+    BraceClose,
+    /// `[`
+    BracketOpen,
+    /// `]`
+    BracketClose,
+    /// `:`
+    Colon,
+    /// `,`
+    Comma,
+    /// A string literal, with escape sequences decoded.
+    String(String),
+    /// A number literal.
+    Number(Number),
+    /// `true` or `false`.
+    Bool(bool),
+    /// `null`
+    Null,
+}
+
+/// An iterator over the [Token]s of JSON text, paired with the byte offset where each
+/// token starts. Whitespace is skipped.
+///
+/// The tokenizer doesn't check that the tokens form a valid document. It stops after
+/// the first invalid token, which is reported as an error.
+/// ```
+/// # use bourne::parse::{Token, Tokenizer};
+/// let tokens = Tokenizer::new("[null]").collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(tokens, [(0, Token::BracketOpen), (1, Token::Null), (5, Token::BracketClose)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    parser: Parser<'a>,
+    failed: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Create a [Tokenizer] over `source`.
+    pub fn new(source: &'a str) -> Self {
+        Self::with_options(source, ParseOptions::default())
+    }
+
+    /// Create a [Tokenizer] over `source` with the given [ParseOptions]. Only the options
+    /// that apply to single tokens, such as [ParseOptions::strict_numbers], have an effect.
+    pub fn with_options(source: &'a str, options: ParseOptions) -> Self {
+        Self {
+            parser: Parser::with_options(source, options),
+            failed: false,
+        }
+    }
+
+    /// The byte offset of the next unread byte: the end of the last token, or of the
+    /// whitespace after it once the next token has been requested.
+    pub fn offset(&self) -> usize {
+        self.parser.index
+    }
+
+    /// The line and column of [Tokenizer::offset], for building errors in parsers on top
+    /// of the tokenizer. See also the constructors on [ParseError].
+    pub fn position(&self) -> Position {
+        Position::locate(self.parser.source, self.parser.index)
+    }
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = ParseResult<(usize, Token)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let parser = &mut self.parser;
+        parser.eat_whitespace();
+        let position = parser.index;
+        let token = match parser.peek()? {
+            b'{' | b'}' | b'[' | b']' | b':' | b',' => Ok(match parser.next()? {
+                b'{' => Token::BraceOpen,
+                b'}' => Token::BraceClose,
+                b'[' => Token::BracketOpen,
+                b']' => Token::BracketClose,
+                b':' => Token::Colon,
+                _ => Token::Comma,
+            }),
+            b'"' => parser.parse_string().map(Token::String),
+            b't' | b'f' => parser.parse_boolean().map(Token::Bool),
+            b'n' => parser.parse_null().map(|_| Token::Null),
+            b'+' | b'-' | b'0'..=b'9' => parser.parse_number().map(Token::Number),
+            b'N' | b'I' if parser.options.allow_nonfinite => parser.parse_number().map(Token::Number),
+            _ => Err(ParseError::InvalidCharacter(position)),
+        };
+        self.failed = token.is_err();
+        Some(token.map(|token| (position, token)))
+    }
+}
+
 impl FromStr for Value {
     type Err = ParseError;
     /// Parse a JSON [Value] from a string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser::new(s).parse_document()
+    }
+}
+
+impl FromStr for Number {
+    type Err = ParseError;
+    /// Parse a JSON number literal, with the same rules as numbers inside a document.
+    /// The whole string must be the number; surrounding whitespace is not allowed.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut parser = Parser::new(s);
-        parser.eat_whitespace();
-        let res = parser.parse_value()?;
-        parser.eat_whitespace();
+        let number = parser.parse_number()?;
         if !parser.is_eof() {
-            Err(ParseError::InvalidCharacter(parser.index))
-        } else {
-            Ok(res)
+            return Err(ParseError::InvalidCharacter(parser.index));
         }
+        Ok(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_fast_path_test() -> ParseResult<()> {
+        let strings = [
+            "",
+            "plain ascii",
+            "multi-byte: caf\u{e9} \u{1f600}",
+            r#"escaped: \"quoted\" \\ \/ \n \t é 😀"#,
+            r#"trailing backslash pair \\"#,
+        ];
+        for string in strings {
+            let source = format!("\"{string}\"");
+            let mut parser = Parser::new(&source);
+            let parsed = parser.parse_str()?;
+            assert_eq!(matches!(parsed, Cow::Borrowed(_)), !string.contains('\\'));
+            assert_eq!(parsed, unescape_string(string)?);
+            assert!(parser.is_eof());
+        }
+        // A larger document mixing both paths.
+        let document = format!("[{}]", (0..1000).map(|i| {
+            if i % 2 == 0 { format!(r#""item {i}""#) } else { format!(r#""item\t{i}""#) }
+        }).collect::<Vec<_>>().join(","));
+        let Value::Array(array) = Value::from_str(&document)? else {
+            panic!("Not an array.");
+        };
+        assert_eq!(array.len(), 1000);
+        assert_eq!(array[998], Value::from("item 998"));
+        assert_eq!(array[999], Value::from("item\t999"));
+        Ok(())
+    }
+
+    #[test]
+    fn from_slice_test() -> ParseResult<()> {
+        assert_eq!(Value::from_slice(b"{\"a\": [1, \"caf\xC3\xA9\"]}")?, Value::from_str(r#"{"a": [1, "café"]}"#)?);
+        assert_eq!(Value::from_slice(b"\xEF\xBB\xBF[true]")?, Value::from(vec![Value::from(true)]));
+        assert!(matches!(Value::from_slice(b"\"\xC3\x28\""), Err(ParseError::InvalidUtf8(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn require_compound_root_test() -> ParseResult<()> {
+        let strict = ParseOptions { require_compound_root: true, ..ParseOptions::default() };
+        assert!(matches!(Value::from_str_with_options(r#""just a string""#, strict), Err(ParseError::NonCompoundRoot)));
+        assert!(matches!(Value::from_str_with_options(" 42 ", strict), Err(ParseError::NonCompoundRoot)));
+        assert_eq!(Value::from_str_with_options(r#" {"a": [1]} "#, strict)?, Value::from_str(r#"{"a": [1]}"#)?);
+        assert_eq!(Value::from_str_with_options("[]", strict)?, Value::array());
+        assert_eq!(Value::from_str(r#""just a string""#)?, Value::from("just a string"));
+        Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn expected_test() {
+        let expected = |source: &str| match Value::from_str(source) {
+            Err(ParseError::Expected { expected, found, position }) => (expected, found, position),
+            other => panic!("Unexpected result: {other:?}"),
+        };
+        assert_eq!(expected(r#"{"Missing colon" null}"#), ("':'", 'n', 17));
+        assert_eq!(expected(r#"{"Comma instead of colon", null}"#), ("':'", ',', 25));
+        assert_eq!(expected(r#"["Colon instead of comma": false]"#), ("',' or ']'", ':', 25));
+        assert_eq!(expected(r#"{"a": 1 "b": 2}"#), ("',' or '}'", '"', 8));
+        assert_eq!(expected(r#"{"é" ü}"#), ("':'", 'ü', 6));
+        assert_eq!(
+            Value::from_str(r#"{"Missing colon" null}"#).unwrap_err().to_string(),
+            "Expected ':' but found 'n' at index 17.",
+        );
+    }
+
+    #[test]
+    fn number_options_test() -> ParseResult<()> {
+        let strict = ParseOptions { strict_numbers: true, ..ParseOptions::default() };
+        assert_eq!(Value::from_str("+1")?, Value::from(1i64));
+        assert!(matches!(Value::from_str_with_options("+1", strict), Err(ParseError::InvalidCharacter(0))));
+        assert!(matches!(Value::from_str_with_options("[01]", strict), Err(ParseError::InvalidCharacter(2))));
+        assert!(matches!(Value::from_str_with_options("1.", strict), Err(ParseError::InvalidCharacter(2))));
+        assert!(matches!(Value::from_str_with_options("1e+", strict), Err(ParseError::InvalidCharacter(3))));
+        assert_eq!(Value::from_str_with_options("[-0.5e+10, 0, 10]", strict)?, Value::from_str("[-0.5e10, 0, 10]")?);
+
+        let nonfinite = ParseOptions { allow_nonfinite: true, ..ParseOptions::default() };
+        assert!(Value::from_str("NaN").is_err());
+        assert!(Value::from_str("-Infinity").is_err());
+        assert!(matches!(Value::from_str_with_options("NaN", nonfinite)?, Value::Number(Number::Float(nan)) if nan.is_nan()));
+        assert_eq!(Value::from_str_with_options("Infinity", nonfinite)?, Value::from(f64::INFINITY));
+        assert_eq!(Value::from_str_with_options("[-Infinity]", nonfinite)?, Value::from(vec![Value::from(f64::NEG_INFINITY)]));
+        assert_eq!(Value::from_str_with_options("+Infinity", nonfinite)?, Value::from(f64::INFINITY));
+        let both = ParseOptions { strict_numbers: true, ..nonfinite };
+        assert!(Value::from_str_with_options("+Infinity", both).is_err());
+        assert_eq!(Value::from_str_with_options("-Infinity", both)?, Value::from(f64::NEG_INFINITY));
+        Ok(())
+    }
+
+    #[test]
+    fn string_error_position_test() {
+        let source = "{\n    \"a\": 1,\n    \"b\": \"é\n\"\n}";
+        let Err(ParseError::LineBreakWhileParsingString { start, position }) = Value::from_str(source) else {
+            panic!("Expected a line break error.");
+        };
+        assert_eq!((start.line, start.column, start.index), (3, 10, 23));
+        assert_eq!((position.line, position.column, position.index), (3, 12, 26));
+        assert_eq!(
+            Value::from_str(source).unwrap_err().to_string(),
+            "Line break at line 3, column 12 (index 26) while parsing string starting at line 3, column 10 (index 23). \
+            End quote must be on same line.",
+        );
+        let Err(ParseError::UnexpectedEOFWhileParsingString { start, position }) = Value::from_str("[\n\"abc") else {
+            panic!("Expected an end of stream error.");
+        };
+        assert_eq!((start.line, start.column, start.index), (2, 1, 2));
+        assert_eq!((position.line, position.column, position.index), (2, 5, 6));
+    }
+
+    #[test]
+    fn from_ndjson_test() -> ParseResult<()> {
+        let values = Value::from_ndjson("{\"level\": \"info\"}\n\n[1, 2]\r\n\"done\"\n")?;
+        assert_eq!(values, vec![
+            Value::from_str(r#"{"level": "info"}"#)?,
+            Value::from_str("[1, 2]")?,
+            Value::from("done"),
+        ]);
+        assert!(Value::from_ndjson("  \n")?.is_empty());
+        let error = Value::from_ndjson("{\"a\": 1}\n{\"a\" 2}\n{\"a\": 3}").unwrap_err();
+        assert!(matches!(
+            &error,
+            ParseError::Record { record: 2, error } if matches!(**error, ParseError::Expected { position: 14, .. }),
+        ));
+        assert_eq!(error.to_string(), "Error in record 2: Expected ':' but found '2' at index 14.");
+        assert_eq!(Value::from_ndjson("1 \t\n2\t")?, vec![Value::from(1i64), Value::from(2i64)]);
+        for source in ["1 2", "[1][2]", "1 2\n3", "1\r2"] {
+            let error = Value::from_ndjson(source).unwrap_err();
+            assert!(matches!(error, ParseError::Record { record: 1, .. }), "{source:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn number_range_test() -> ParseResult<()> {
+        assert!(matches!(Value::from_str("1e999"), Err(ParseError::NumberOutOfRange(0))));
+        assert!(matches!(Value::from_str("[1, -1.5e400]"), Err(ParseError::NumberOutOfRange(4))));
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            assert!(matches!(Value::from_str("1e-999")?, Value::Number(Number::Float(zero)) if zero == 0.0 && zero.is_sign_positive()));
+            let negative_zero = Value::from_str("-0")?;
+            assert!(matches!(negative_zero, Value::Number(Number::Float(zero)) if zero == 0.0 && zero.is_sign_negative()));
+            assert_eq!(negative_zero.to_string(), "-0.0");
+            assert!(matches!(Value::from_str(&negative_zero.to_string())?, Value::Number(Number::Float(zero)) if zero.is_sign_negative()));
+            assert!(matches!(Value::from_str("0")?, Value::Number(Number::Int(0))));
+        }
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            // The text keeps its exact value, even though it rounds to 0.0.
+            let tiny = Value::from_str("1e-999")?;
+            assert!(tiny != 0.0 && tiny.as_f64().is_some_and(|tiny| tiny == 0.0 && tiny.is_sign_positive()));
+            let negative_zero = Value::from_str("-0")?;
+            assert!(negative_zero.strict_eq(&Value::from(0.0)));
+            assert!(negative_zero.as_f64().is_some_and(f64::is_sign_negative));
+            assert_eq!(negative_zero.to_string(), "-0");
+            assert!(Value::from_str(&negative_zero.to_string())?.as_f64().is_some_and(f64::is_sign_negative));
+            assert!(Value::from_str("0")?.strict_eq(&Value::from(0i64)));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "arbitrary_precision"))]
+    #[test]
+    fn preserve_number_text_test() -> ParseResult<()> {
+        let options = ParseOptions { preserve_number_text: true, ..ParseOptions::default() };
+        let source = "[3.14000, 1.0E5, -0, 18446744073709551615]";
+        let value = Value::from_str_with_options(source, options)?;
+        assert!(matches!(&value[0], Value::Number(Number::Raw(text)) if text == "3.14000"));
+        assert_eq!(value.to_string(), "[3.14000,1.0E5,-0,18446744073709551615]");
+        assert_eq!(Value::from_str(source)?.to_string(), "[3.14,100000.0,-0.0,18446744073709551615]");
+        // The text compares exactly, so it isn't equal to the nearest f64.
+        assert_ne!(value[0], Value::from_str("3.14")?);
+        assert_eq!(value[0].as_f64(), "3.14".parse().ok());
+        assert_eq!(value[1], 100000i64);
+        assert_eq!(value[1].as_i64(), Some(100000));
+        assert_eq!(value, Value::from_str_with_options("[3.14, 100000, 0, 18446744073709551615]", options)?);
+        assert!(matches!(Value::from_str_with_options("1e999", options), Err(ParseError::NumberOutOfRange(0))));
+        Ok(())
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn arbitrary_precision_test() -> ParseResult<()> {
+        let source = "[0.1,1.0000000001,123456789012345678901234567890]";
+        let value = Value::from_str(source)?;
+        assert!(matches!(&value[2], Value::Number(Number::Raw(text)) if text == "123456789012345678901234567890"));
+        assert_eq!(value.to_string(), source);
+        assert_eq!(Value::from_str(&value.to_string())?.to_string(), source);
+        assert_eq!(value[0].as_f64(), Some(0.1));
+        assert_eq!(value[1].as_f64(), Some(1.0000000001));
+        assert_eq!(value[2].as_i64(), None);
+        assert_eq!(Value::from_str("42")?.as_i64(), Some(42));
+        Ok(())
+    }
+
+    #[test]
+    fn strict_escapes_test() -> ParseResult<()> {
+        let strict = ParseOptions { strict_escapes: true, ..ParseOptions::default() };
+        // fail15.json and fail17.json from the JSON test suite.
+        let fail15 = r#"["Illegal backslash escape: \x15"]"#;
+        let fail17 = r#"["Illegal backslash escape: \017"]"#;
+        assert_eq!(Value::from_str(fail15)?[0], "Illegal backslash escape: x15");
+        assert_eq!(Value::from_str(fail17)?[0], "Illegal backslash escape: 017");
+        assert!(matches!(Value::from_str_with_options(fail15, strict), Err(ParseError::InvalidEscapeSequence(28))));
+        assert!(matches!(Value::from_str_with_options(fail17, strict), Err(ParseError::InvalidEscapeSequence(28))));
+        let valid = r#"["\" \\ \/ \b \f \n \r \t \u00e9"]"#;
+        assert_eq!(Value::from_str_with_options(valid, strict)?, Value::from_str(valid)?);
+        assert!(matches!(Value::from_str(r#""é \ud800x""#), Err(ParseError::InvalidEscapeSequence(4))));
+        assert!(matches!(unescape_string(r#"ab\ud800"#), Err(ParseError::InvalidEscapeSequence(2))));
+        Ok(())
+    }
+
+    #[test]
+    fn strict_control_chars_test() -> ParseResult<()> {
+        let strict = ParseOptions { strict_control_chars: true, ..ParseOptions::default() };
+        // fail25.json and fail26.json from the JSON test suite.
+        let fail25 = "[\"\ttab\tcharacter\tin\tstring\t\"]";
+        let fail26 = "[\"tab\\\tcharacter\\\tin\\\tstring\\\t\"]";
+        assert_eq!(Value::from_str(fail25)?[0], "\ttab\tcharacter\tin\tstring\t");
+        assert_eq!(Value::from_str(fail26)?[0], "tab\tcharacter\tin\tstring\t");
+        assert!(matches!(Value::from_str_with_options(fail25, strict), Err(ParseError::ControlCharacterInString(2))));
+        assert!(matches!(Value::from_str_with_options(fail26, strict), Err(ParseError::ControlCharacterInString(6))));
+        assert!(matches!(Value::from_str_with_options("\"\u{1}\"", strict), Err(ParseError::ControlCharacterInString(1))));
+        assert_eq!(Value::from_str_with_options(r#""escaped\ttab""#, strict)?, "escaped\ttab");
+        Ok(())
+    }
+
+    #[test]
+    fn capacity_hint_test() -> ParseResult<()> {
+        let source = format!("[{}]", (0..10_000).map(|i| i.to_string()).collect::<Vec<_>>().join(", "));
+        let Value::Array(array) = Value::from_str(&source)? else { panic!() };
+        assert_eq!(array.len(), 10_000);
+        assert!(array.iter().enumerate().all(|(i, value)| value.as_u64() == Some(i as u64)));
+
+        let Value::Array(array) = Value::from_str(r#"[ "a,b", [1, 2], {"c": [3, 4]}, "\"]," ]"#)? else { panic!() };
+        assert_eq!((array.len(), array.capacity()), (4, 4));
+        let Value::Array(array) = Value::from_str("[ ]")? else { panic!() };
+        assert_eq!(array.capacity(), 0);
+        let value = Value::from_str(r#"{"a": {"b": 1, "c": 2}, "d": "}"}"#)?;
+        assert_eq!(value.len(), 2);
+        assert_eq!(value["a"].len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_key_test() -> ParseResult<()> {
+        let parse = |source: &str, on_duplicate_key| {
+            Value::from_str_with_options(source, ParseOptions { on_duplicate_key, ..ParseOptions::default() })
+        };
+        let source = r#"{"a":1,"a":2}"#;
+        assert_eq!(parse(source, DuplicateKeyStrategy::Overwrite)?, Value::from_str(r#"{"a": 2}"#)?);
+        assert_eq!(Value::from_str(source)?, Value::from_str(r#"{"a": 2}"#)?);
+        assert_eq!(parse(source, DuplicateKeyStrategy::FirstWins)?, Value::from_str(r#"{"a": 1}"#)?);
+        assert_eq!(parse(source, DuplicateKeyStrategy::Merge)?, Value::from_str(r#"{"a": [1, 2]}"#)?);
+        assert!(matches!(
+            parse(source, DuplicateKeyStrategy::Reject),
+            Err(ParseError::DuplicateKey { key, position: 7 }) if key == "a",
+        ));
+
+        let source = r#"{"a": [1], "b": [2], "a": 3, "a": {"c": [4], "c": 5}}"#;
+        assert_eq!(
+            parse(source, DuplicateKeyStrategy::Merge)?,
+            Value::from_str(r#"{"a": [[1], 3, {"c": [[4], 5]}], "b": [2]}"#)?,
+        );
+        assert_eq!(parse(source, DuplicateKeyStrategy::FirstWins)?, Value::from_str(r#"{"a": [1], "b": [2]}"#)?);
+        assert_eq!(parse(r#"{"a": 1, "b": 2}"#, DuplicateKeyStrategy::Reject)?.len(), 2);
+        Ok(())
+    }
+
+    /// Truncated, mis-encoded and boundary inputs, each of which must fail cleanly.
+    const ADVERSARIAL_CORPUS: &[&[u8]] = &[
+        b"", b" ", b"[", b"{", b"[1,", b"{\"a\"", b"{\"a\":", b"{\"a\":1,", b"{,}", b"[,]",
+        b"\"", b"\"\\", b"\"\\u", b"\"\\u12", b"\"\\ud83d", b"\"\\ud83d\\u", b"\"\\ud83d\\u0041\"", b"\"\\udc00\\",
+        b"\"\xc3", b"\"\\\xc3", b"\"\xc3\"", b"[\"\xe9\"]", b"\xff", b"[\xc3\xa9", b"\"\\\xc3\xa9",
+        b"-", b"+", b".", b"-.5", b"1e", b"1e+", b"1e-", b"1ee2", b"1.2.3", b"--1", b"1-", b"0x10",
+        b"1e400", b"-1e400", b"[1e999999999999999999]",
+        b"[1\xc3\xa9]", b"[-\xc3\xa9]", b"[1.\xc3\xa9]", b"[1e\xc3\xa9]", b"{\"a\"\xc3\xa91}", b"[1,\xc3\xa9]",
+        b"[1 2]", b"{\"a\" 1}", b"{1: 2}", b"{\"a\": 1 \"b\": 2}", b"[}", b"{]", b"[1]]", b"{}}", b"[1] [2]",
+        b"nul", b"tru", b"fals", b"nulll", b"True", b"NaN", b"Infinity", b"-Infinity", b"+Infinity",
+        b"\"a\nb\"", b"\"a\rb\"",
+    ];
+
+    #[test]
+    fn adversarial_corpus_test() {
+        let strict = ParseOptions {
+            strict_numbers: true,
+            strict_whitespace: true,
+            strict_escapes: true,
+            strict_control_chars: true,
+            preserve_number_text: true,
+            ..ParseOptions::default()
+        };
+        for input in ADVERSARIAL_CORPUS {
+            assert!(Value::from_slice(input).is_err(), "{:?}", String::from_utf8_lossy(input));
+            let Ok(source) = core::str::from_utf8(input) else {
+                continue;
+            };
+            for options in [ParseOptions::default(), strict] {
+                let error = Value::from_str_with_options(source, options).unwrap_err();
+                assert!(error.offset().is_none_or(|offset| offset <= source.len()), "{source:?}: {error}");
+                error.with_source_context(source).to_string();
+            }
+            assert!(parse_ref(source).is_err(), "{source:?}");
+            assert!(parse_events(source, |_| ()).is_err(), "{source:?}");
+            // The tokenizer only sees lexical errors, but must not panic either.
+            Tokenizer::new(source).for_each(drop);
+        }
+        // Errors point inside the source even when the input ends early.
+        assert!(matches!(
+            Value::from_str("\"\\"),
+            Err(ParseError::UnexpectedEOFWhileParsingString { position: Position { index: 2, .. }, .. }),
+        ));
+        assert_eq!(Value::from_str("\"\\é\"").unwrap(), Value::from("é"));
+    }
+
+    #[test]
+    fn source_context_test() {
+        let source = "{\n\t\"a\": [1, 2 x],\n}";
+        let error = Value::from_str(source).unwrap_err();
+        assert_eq!(error.offset(), Some(14));
+        let detailed = error.with_source_context(source);
+        assert_eq!(detailed.position().map(|position| (position.line, position.column)), Some((2, 13)));
+        let message = detailed.to_string();
+        let lines = message.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Expected ',' or ']' but found 'x' at index 14.");
+        assert_eq!(lines[1], " --> line 2, column 13 (index 14)");
+        assert_eq!(lines[3], "2 | \t\"a\": [1, 2 x],");
+        assert_eq!(lines[4], "  | \t           ^");
+        assert_eq!(lines[3].find('x'), lines[4].find('^'));
+
+        let source = "[\"é\", \"ü\u{1}\"]";
+        let options = ParseOptions { strict_control_chars: true, ..ParseOptions::default() };
+        let detailed = Value::from_str_with_options(source, options).unwrap_err().with_source_context(source);
+        assert!(detailed.to_string().ends_with("1 | [\"é\", \"ü\u{1}\"]\n  |         ^"));
+
+        let source = "[1,\r\n2";
+        let detailed = Value::from_str(source).unwrap_err().with_source_context(source);
+        assert!(detailed.to_string().ends_with("2 | 2\n  |  ^"));
+        assert!(matches!(detailed.into_error(), ParseError::UnexpectedEOF));
+
+        let limit = ParseOptions { max_input_bytes: Some(1), ..ParseOptions::default() };
+        let detailed = Value::from_str_with_options("[]", limit).unwrap_err().with_source_context("[]");
+        assert_eq!(detailed.to_string(), "Input of 2 bytes exceeds the limit of 1 bytes.");
+        assert_eq!(detailed.position(), None);
+
+        // Offsets past the end or inside a character, as from the wrong source, don't panic.
+        let detailed = ParseError::InvalidCharacter(10).with_source_context("[1]");
+        assert!(detailed.to_string().ends_with("1 | [1]\n  |    ^"));
+        let detailed = ParseError::InvalidCharacter(2).with_source_context("[é]");
+        assert!(detailed.to_string().ends_with("1 | [é]\n  |   ^"));
+    }
+
+    #[test]
+    fn error_kind_test() {
+        use crate::error::ErrorKind;
+        let kind = |source: &str| Value::from_str(source).unwrap_err().kind();
+        assert_eq!(kind("[1 2]"), ErrorKind::Syntax);
+        assert_eq!(kind("[1, 2"), ErrorKind::Eof);
+        assert_eq!(kind(r#"{"a": "b"#), ErrorKind::Eof);
+        assert_eq!(kind("1e999"), ErrorKind::Number);
+        assert_eq!(kind(r#""\u12x4""#), ErrorKind::Escape);
+        let shallow = ParseOptions { max_depth: Some(1), ..ParseOptions::default() };
+        assert_eq!(Value::from_str_with_options("[[]]", shallow).unwrap_err().kind(), ErrorKind::Depth);
+        assert_eq!(Value::from_ndjson("1\n[").unwrap_err().kind(), ErrorKind::Eof);
+    }
+
+    #[test]
+    fn limits_test() -> ParseResult<()> {
+        let source = format!("[{}]", vec!["\"abcdefgh\""; 100].join(","));
+        assert!((1024..2048).contains(&source.len()));
+        let small = ParseOptions { max_input_bytes: Some(512), ..ParseOptions::default() };
+        let large = ParseOptions { max_input_bytes: Some(2048), ..ParseOptions::default() };
+        assert!(matches!(
+            Value::from_str_with_options(&source, small),
+            Err(ParseError::InputTooLarge { size, limit: 512 }) if size == source.len(),
+        ));
+        assert_eq!(Value::from_str_with_options(&source, large)?.len(), 100);
+
+        let shallow = ParseOptions { max_depth: Some(2), ..ParseOptions::default() };
+        assert_eq!(Value::from_str_with_options(r#"[{"a": 1}, [2], {}]"#, shallow)?.len(), 3);
+        assert!(matches!(Value::from_str_with_options(r#"[{"a": [1]}]"#, shallow), Err(ParseError::DepthLimitExceeded(7))));
+        let deep = "[".repeat(100_000);
+        assert!(matches!(Value::from_str_with_options(&deep, shallow), Err(ParseError::DepthLimitExceeded(2))));
+        Ok(())
+    }
+
+    #[test]
+    fn strict_whitespace_test() -> ParseResult<()> {
+        let strict = ParseOptions { strict_whitespace: true, ..ParseOptions::default() };
+        assert_eq!(Value::from_str("[1,\x0c2]")?, Value::from_str("[1, 2]")?);
+        assert_eq!(Value::from_str("[1\x0c, 2]")?, Value::from_str("[1, 2]")?);
+        assert!(matches!(Value::from_str_with_options("[1,\x0c2]", strict), Err(ParseError::InvalidCharacter(3))));
+        assert!(matches!(Value::from_str_with_options("[1\x0c, 2]", strict), Err(ParseError::InvalidCharacter(2))));
+        assert!(matches!(
+            Value::from_str_with_options("[true\x0c]", strict),
+            Err(ParseError::Expected { found: '\x0c', position: 5, .. }),
+        ));
+        assert!(Value::from_str("[1,\x0b2]").is_err());
+        assert_eq!(Value::from_str_with_options(" \t[1,\r\n2] ", strict)?, Value::from_str("[1, 2]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn multibyte_error_test() {
+        for source in [r#"{"ключ": é}"#, r#"{"ключ" é}"#, r#"{"ключ": 1é}"#, r#"{"ключ": 1 é}"#, r#"{"ключ": "é"é"#, "[1é]", "é"] {
+            assert!(Value::from_str(source).is_err(), "{source}");
+            assert!(parse_events(source, |_| ()).is_err(), "{source}");
+        }
+        assert!(matches!(Value::from_str(r#"{"ключ" é}"#), Err(ParseError::Expected { found: 'é', position: 12, .. })));
+        assert!(matches!(Value::from_str(r#"{"ключ": 1é}"#), Err(ParseError::InvalidCharacter(14))));
+        assert!(matches!(parse_events("[é]", |_| ()), Err(ParseError::InvalidCharacter(1))));
+    }
+
+    #[test]
+    fn tokenizer_test() -> ParseResult<()> {
+        let tokens = Tokenizer::new(r#"{"a":[1,true]}"#).collect::<ParseResult<Vec<_>>>()?;
+        assert_eq!(tokens, [
+            (0, Token::BraceOpen),
+            (1, Token::String("a".to_string())),
+            (4, Token::Colon),
+            (5, Token::BracketOpen),
+            (6, Token::Number(Number::Int(1))),
+            (7, Token::Comma),
+            (8, Token::Bool(true)),
+            (12, Token::BracketClose),
+            (13, Token::BraceClose),
+        ]);
+        let tokens = Tokenizer::new(" null , \"\\u00e9\" ").collect::<ParseResult<Vec<_>>>()?;
+        assert_eq!(tokens, [(1, Token::Null), (6, Token::Comma), (8, Token::String("é".to_string()))]);
+        let mut tokens = Tokenizer::new("[nul]");
+        assert!(matches!(tokens.next(), Some(Ok((0, Token::BracketOpen)))));
+        assert!(matches!(tokens.next(), Some(Err(ParseError::InvalidCharacter(1)))));
+        assert!(tokens.next().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn error_constructor_test() {
+        let source = "{\n  \"a\" 1}";
+        let mut tokens = Tokenizer::new(source);
+        assert!(matches!(tokens.next(), Some(Ok((0, Token::BraceOpen)))));
+        assert!(matches!(tokens.next(), Some(Ok((4, Token::String(_))))));
+        assert_eq!(tokens.offset(), 7);
+        assert_eq!(tokens.position(), Position { index: 7, line: 2, column: 6 });
+        assert!(matches!(tokens.next(), Some(Ok((8, Token::Number(_))))));
+        let error = ParseError::expected_at("':'", source, 8);
+        assert_eq!(error.to_string(), "Expected ':' but found '1' at index 8.");
+        assert!(matches!(ParseError::expected_at("'}'", source, source.len()), ParseError::UnexpectedEOF));
+        assert_eq!(ParseError::invalid_character_at(3).to_string(), "Invalid character at index 3.");
+        assert_eq!(
+            ParseError::unterminated_string_at(source, 4).to_string(),
+            "Unexpected end of stream at line 2, column 9 (index 10) while parsing string starting at line 2, column 3 (index 4).",
+        );
+        assert_eq!(
+            ParseError::line_break_in_string_at(source, 0, 1).to_string(),
+            "Line break at line 1, column 2 (index 1) while parsing string starting at line 1, column 1 (index 0). End quote must be on same line.",
+        );
+    }
+
+    #[cfg(not(feature = "arbitrary_precision"))]
+    #[test]
+    fn collapse_integral_exponents_test() -> ParseResult<()> {
+        let collapse = ParseOptions { collapse_integral_exponents: true, ..ParseOptions::default() };
+        let parse = |source: &str, options| match Value::from_str_with_options(source, options) {
+            Ok(Value::Number(number)) => Ok(number),
+            Ok(other) => panic!("{other}"),
+            Err(error) => Err(error),
+        };
+        assert!(matches!(parse("1e2", ParseOptions::default())?, Number::Float(100.0)));
+        assert!(matches!(parse("1e2", collapse)?, Number::Int(100)));
+        assert!(matches!(parse("1.5e1", collapse)?, Number::Int(15)));
+        assert!(matches!(parse("1.55e1", collapse)?, Number::Float(15.5)));
+        assert!(matches!(parse("-25E-1", collapse)?, Number::Float(-2.5)));
+        assert!(matches!(parse("-2E+3", collapse)?, Number::Int(-2000)));
+        assert!(matches!(parse("1.8e19", collapse)?, Number::Uint(18_000_000_000_000_000_000)));
+        assert!(matches!(parse("1e20", collapse)?, Number::Float(1e20)));
+        assert!(matches!(parse("-0e1", collapse)?, Number::Float(zero) if zero.is_sign_negative()));
+        assert!(matches!(parse("2.0", collapse)?, Number::Float(2.0)));
+        assert!(matches!(parse("1e400", collapse), Err(ParseError::NumberOutOfRange(0))));
+        Ok(())
+    }
+
+    #[test]
+    fn number_from_str_test() -> ParseResult<()> {
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            assert!(matches!(Number::from_str("42")?, Number::Int(42)));
+            assert!(matches!(Number::from_str("3.14")?, Number::Float(x) if x == "3.14".parse::<f64>().unwrap()));
+            assert!(matches!(Number::from_str("18446744073709551615")?, Number::Uint(u64::MAX)));
+        }
+        assert_eq!(Number::from_str("-7")?.as_i64(), Some(-7));
+        assert!(matches!(Number::from_str("3.14abc"), Err(ParseError::InvalidCharacter(4))));
+        assert!(matches!(Number::from_str("42 "), Err(ParseError::InvalidCharacter(2))));
+        assert!(matches!(Number::from_str("1,2"), Err(ParseError::InvalidCharacter(1))));
+        assert!(matches!(Number::from_str(""), Err(ParseError::InvalidCharacter(0))));
+        assert!(matches!(Number::from_str("1e999"), Err(ParseError::NumberOutOfRange(0))));
+        Ok(())
+    }
+
+    #[test]
+    fn from_str_spanned_test() -> ParseResult<()> {
+        for source in ["  {\"a\": [1, 2]}\n", "[]", "\t\"text\" ", " 42 ", "null"] {
+            let (value, span) = Value::from_str_spanned(source)?;
+            assert_eq!(&source[span], source.trim());
+            assert_eq!(value, Value::from_str(source)?);
+        }
+        assert!(matches!(Value::from_str_spanned(" [1] 2"), Err(ParseError::InvalidCharacter(5))));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_ref_test() -> ParseResult<()> {
+        let source = r#"{"plain": ["a", "b c"], "escaped": "tab\there", "key\u0041": 1.5, "n": [null, true, -3]}"#;
+        let value = parse_ref(source)?;
+        let ValueRef::Object(entries) = &value else { panic!() };
+        assert!(matches!(&entries[0].0, Cow::Borrowed("plain")));
+        assert!(matches!(&entries[2].0, Cow::Owned(key) if key == "keyA"));
+        let Some(ValueRef::Array(plain)) = value.get("plain") else { panic!() };
+        assert!(plain.iter().all(|string| matches!(string, ValueRef::String(Cow::Borrowed(_)))));
+        assert!(matches!(value.get("escaped"), Some(ValueRef::String(Cow::Owned(string))) if string == "tab\there"));
+        assert_eq!(value.to_owned(), Value::from_str(source)?);
+
+        let duplicates = parse_ref(r#"{"a": 1, "a": 2}"#)?;
+        assert_eq!(duplicates.get("a"), Some(&ValueRef::Number(Number::Int(2))));
+        assert_eq!(duplicates.to_owned(), Value::from_str(r#"{"a": 2}"#)?);
+        assert!(matches!(parse_ref("[1, 2"), Err(ParseError::UnexpectedEOF)));
+        assert!(matches!(parse_ref("[1] x"), Err(ParseError::InvalidCharacter(4))));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_events_test() -> ParseResult<()> {
+        let source = r#"
+            [
+                "JSON Test Pattern pass1",
+                {"object with 1 member": ["array with 1 element"]},
+                {},
+                [],
+                -42,
+                true,
+                null,
+                {
+                    "integer": 1234567890,
+                    "E": 1.23456789E34,
+                    "": 23456789012E66,
+                    "zero": 0,
+                    "space": " ",
+                    "quote": "\"",
+                    "controls": "\b\f\n\r\t",
+                    "hex": "ģ䕧覫췯ꯍ",
+                    "url": "http://www.JSON.org/",
+                    "array": [ "a", "b" ],
+                    "compact": [1,2,3,4,5,6,7],
+                    "\/\\\"쫾몾ꮘﳞ볚\b\f\n\r\t`1~!@#$%^&*()_+-=[]{}|;:',./<>?": "A key can be any string"
+                },
+                0.5, 98.6, 1e1, 1e-1, 1e00, 2e+00, 2e-00,
+                "rosebud"
+            ]
+        "#;
+        let mut strings = 0;
+        let mut keys = Vec::new();
+        let mut depth = 0;
+        parse_events(source, |event| match event {
+            Event::Scalar(Value::String(_)) => strings += 1,
+            Event::Key(key) => keys.push(key.to_owned()),
+            Event::StartArray | Event::StartObject => depth += 1,
+            Event::EndArray | Event::EndObject => depth -= 1,
+            Event::Scalar(_) => (),
+        })?;
+        assert_eq!(strings, 11);
+        assert_eq!(keys.len(), 13);
+        assert!(keys.iter().any(|key| key == "zero"));
+        assert!(keys.iter().any(|key| key.starts_with("/\\\"")));
+        assert_eq!(depth, 0);
+        assert!(matches!(parse_events(r#"{"a" 1}"#, |_| ()), Err(ParseError::Expected { expected: "':'", .. })));
+        Ok(())
+    }
+}
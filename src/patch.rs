@@ -0,0 +1,260 @@
+use alloc::borrow::Cow;
+
+use crate::error::PatchError;
+use crate::pointer::{parse_index, pointer_tokens};
+use crate::{into_key, Value};
+use crate::prelude::*;
+
+/// One operation of a JSON Patch (RFC 6902), applied by [Value::apply_patch].
+///
+/// Paths are JSON Pointers (RFC 6901). Operation objects from a patch document can be
+/// converted with [TryFrom].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp {
+    /// Add `value` at `path`: insert or replace an object key, or insert into an array,
+    /// where the index `-` appends.
+    Add { path: String, value: Value },
+    /// Remove the value at `path`, which must exist.
+    Remove { path: String },
+    /// Replace the value at `path`, which must exist.
+    Replace { path: String, value: Value },
+    /// Remove the value at `from` and add it at `path`.
+    Move { from: String, path: String },
+    /// Add a copy of the value at `from` at `path`.
+    Copy { from: String, path: String },
+    /// Check that the value at `path` equals `value`.
+    Test { path: String, value: Value },
+}
+
+impl TryFrom<&Value> for PatchOp {
+    type Error = PatchError;
+    /// Convert an operation object such as `{"op": "add", "path": "/a", "value": 1}`.
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let member = |name: &str| value.get(name).ok_or_else(|| PatchError::InvalidOperation(format!("missing \"{name}\"")));
+        let pointer = |name: &str| member(name)?.as_str().map(str::to_owned)
+            .ok_or_else(|| PatchError::InvalidOperation(format!("\"{name}\" is not a string")));
+        let op = member("op")?.as_str().ok_or_else(|| PatchError::InvalidOperation("\"op\" is not a string".to_owned()))?;
+        Ok(match op {
+            "add" => PatchOp::Add { path: pointer("path")?, value: member("value")?.clone() },
+            "remove" => PatchOp::Remove { path: pointer("path")? },
+            "replace" => PatchOp::Replace { path: pointer("path")?, value: member("value")?.clone() },
+            "move" => PatchOp::Move { from: pointer("from")?, path: pointer("path")? },
+            "copy" => PatchOp::Copy { from: pointer("from")?, path: pointer("path")? },
+            "test" => PatchOp::Test { path: pointer("path")?, value: member("value")?.clone() },
+            other => return Err(PatchError::InvalidOperation(format!("unknown op {other:?}"))),
+        })
+    }
+}
+
+/// Split a non-empty JSON Pointer into its parent pointer and unescaped last token.
+/// Returns [None] for the empty pointer, which refers to the whole document.
+fn split_pointer(path: &str) -> Result<Option<(&str, Cow<'_, str>)>, PatchError> {
+    if path.is_empty() {
+        return Ok(None);
+    }
+    let invalid = || PatchError::InvalidPointer(path.to_owned());
+    let slash = path.rfind('/').ok_or_else(invalid)?;
+    let token = pointer_tokens(&path[slash..]).and_then(|mut tokens| tokens.next()).ok_or_else(invalid)?;
+    Ok(Some((&path[..slash], token)))
+}
+
+/// Find the container holding the last token of `path`.
+fn parent_mut<'a>(value: &'a mut Value, parent: &str, path: &str) -> Result<&'a mut Value, PatchError> {
+    value.pointer_mut(parent).ok_or_else(|| PatchError::PathNotFound(path.to_owned()))
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), PatchError> {
+    let Some((parent, token)) = split_pointer(path)? else {
+        *document = value;
+        return Ok(());
+    };
+    match parent_mut(document, parent, path)? {
+        Value::Object(object) => {
+            object.insert(into_key(token.into_owned()), value);
+        }
+        Value::Array(array) if token == "-" => array.push(value),
+        Value::Array(array) => match parse_index(&token) {
+            Some(index) if index <= array.len() => array.insert(index, value),
+            _ => return Err(PatchError::PathNotFound(path.to_owned())),
+        },
+        _ => return Err(PatchError::PathNotFound(path.to_owned())),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, PatchError> {
+    // Only checks that the pointer is valid; the whole document can't be removed either way.
+    split_pointer(path)?;
+    document.remove_path(path).ok_or_else(|| PatchError::PathNotFound(path.to_owned()))
+}
+
+fn get<'a>(document: &'a Value, path: &str) -> Result<&'a Value, PatchError> {
+    if !path.is_empty() && !path.starts_with('/') {
+        return Err(PatchError::InvalidPointer(path.to_owned()));
+    }
+    document.pointer(path).ok_or_else(|| PatchError::PathNotFound(path.to_owned()))
+}
+
+fn apply(document: &mut Value, op: &PatchOp) -> Result<(), PatchError> {
+    match op {
+        PatchOp::Add { path, value } => add(document, path, value.clone()),
+        PatchOp::Remove { path } => remove(document, path).map(drop),
+        PatchOp::Replace { path, value } => {
+            *document.pointer_mut(path).ok_or_else(|| PatchError::PathNotFound(path.clone()))? = value.clone();
+            Ok(())
+        }
+        PatchOp::Move { from, path } if from == path => get(document, from).map(drop),
+        PatchOp::Move { from, path } => {
+            if path.strip_prefix(from.as_str()).is_some_and(|rest| rest.starts_with('/')) {
+                return Err(PatchError::InvalidMove { from: from.clone(), path: path.clone() });
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        PatchOp::Copy { from, path } => {
+            let value = get(document, from)?.clone();
+            add(document, path, value)
+        }
+        PatchOp::Test { path, value } => match get(document, path)? == value {
+            true => Ok(()),
+            false => Err(PatchError::TestFailed(path.clone())),
+        },
+    }
+}
+
+impl Value {
+    /// Apply a JSON Merge Patch (RFC 7386) to self.
+    ///
+    /// If `patch` is an object, each of its keys is merged into self recursively, turning
+    /// self into an object first if it isn't one. A [Value::Null] in `patch` removes the key
+    /// instead. Any other `patch` replaces self entirely. Unlike [Value::merge], there is no
+    /// way to set a key to [Value::Null].
+    pub fn apply_merge_patch(&mut self, patch: &Value) {
+        let Value::Object(patch) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, Value::Object(_)) {
+            *self = Value::object();
+        }
+        for (key, value) in patch {
+            if let Value::Null = value {
+                self.remove(key);
+            } else {
+                self.entry(&**key).or_insert(Value::Null).apply_merge_patch(value);
+            }
+        }
+    }
+
+    /// Apply a JSON Patch (RFC 6902) to self.
+    ///
+    /// The operations are applied in order. If any of them fails, including a
+    /// [PatchOp::Test] that doesn't match, the error is returned and self is left unchanged.
+    pub fn apply_patch(&mut self, ops: &[PatchOp]) -> Result<(), PatchError> {
+        let mut document = self.clone();
+        ops.iter().try_for_each(|op| apply(&mut document, op))?;
+        *self = document;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::PatchOp;
+    use crate::error::PatchError;
+    use crate::Value;
+
+    fn ops(patch: &str) -> Vec<PatchOp> {
+        let patch = Value::from_str(patch).unwrap();
+        patch.as_array().unwrap().iter().map(|op| PatchOp::try_from(op).unwrap()).collect()
+    }
+
+    #[test]
+    fn apply_patch_test() -> Result<(), crate::error::ParseError> {
+        // Appendix A of RFC 6902.
+        let cases = [
+            (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#, r#"{"baz": "qux", "foo": "bar"}"#),
+            (r#"{"foo": ["bar", "baz"]}"#, r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#, r#"{"foo": ["bar", "qux", "baz"]}"#),
+            (r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "remove", "path": "/baz"}]"#, r#"{"foo": "bar"}"#),
+            (r#"{"foo": ["bar", "qux", "baz"]}"#, r#"[{"op": "remove", "path": "/foo/1"}]"#, r#"{"foo": ["bar", "baz"]}"#),
+            (r#"{"baz": "qux", "foo": "bar"}"#, r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#, r#"{"baz": "boo", "foo": "bar"}"#),
+            (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+            ),
+            (r#"{"foo": ["all", "grass", "cows", "eat"]}"#, r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#, r#"{"foo": ["all", "cows", "eat", "grass"]}"#),
+            (r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#, r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]"#, r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#),
+            (r#"{"foo": "bar"}"#, r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#, r#"{"foo": "bar", "child": {"grandchild": {}}}"#),
+            (r#"{"foo": ["bar"]}"#, r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#, r#"{"foo": ["bar", ["abc", "def"]]}"#),
+            (r#"{"/": 9, "~1": 10}"#, r#"[{"op": "test", "path": "/~01", "value": 10}]"#, r#"{"/": 9, "~1": 10}"#),
+            (r#"{"foo": {"bar": 1}}"#, r#"[{"op": "copy", "from": "/foo", "path": "/baz"}, {"op": "add", "path": "/baz/bar", "value": 2}]"#, r#"{"foo": {"bar": 1}, "baz": {"bar": 2}}"#),
+            (r#"{"foo": 1}"#, r#"[{"op": "add", "path": "", "value": [1]}]"#, r#"[1]"#),
+        ];
+        for (target, patch, expected) in cases {
+            let mut value = Value::from_str(target)?;
+            value.apply_patch(&ops(patch)).unwrap();
+            assert_eq!(value, Value::from_str(expected)?, "patch {patch}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn apply_patch_error_test() -> Result<(), crate::error::ParseError> {
+        let original = Value::from_str(r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#)?;
+        let cases = [
+            (r#"[{"op": "remove", "path": "/baz"}, {"op": "test", "path": "/foo/1", "value": "2"}]"#, PatchError::TestFailed("/foo/1".into())),
+            (r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#, PatchError::PathNotFound("/baz/bat".into())),
+            (r#"[{"op": "add", "path": "/foo/4", "value": 1}]"#, PatchError::PathNotFound("/foo/4".into())),
+            (r#"[{"op": "replace", "path": "/missing", "value": 1}]"#, PatchError::PathNotFound("/missing".into())),
+            (r#"[{"op": "remove", "path": ""}]"#, PatchError::PathNotFound("".into())),
+            (r#"[{"op": "move", "from": "/foo", "path": "/foo/0"}]"#, PatchError::InvalidMove { from: "/foo".into(), path: "/foo/0".into() }),
+            (r#"[{"op": "copy", "from": "baz", "path": "/x"}]"#, PatchError::InvalidPointer("baz".into())),
+        ];
+        for (patch, error) in cases {
+            let mut value = original.clone();
+            assert_eq!(value.apply_patch(&ops(patch)), Err(error), "patch {patch}");
+            assert_eq!(value, original);
+        }
+        let invalid = Value::from_str(r#"{"op": "frobnicate", "path": "/a"}"#)?;
+        assert!(matches!(PatchOp::try_from(&invalid), Err(PatchError::InvalidOperation(_))));
+        let missing = Value::from_str(r#"{"op": "add", "path": "/a"}"#)?;
+        assert!(matches!(PatchOp::try_from(&missing), Err(PatchError::InvalidOperation(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn apply_merge_patch_test() -> Result<(), crate::error::ParseError> {
+        // Section 3 and Appendix A of RFC 7386.
+        let cases = [
+            (
+                r#"{"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}, "tags": ["example", "sample"], "content": "This will be unchanged"}"#,
+                r#"{"title": "Hello!", "phoneNumber": "+01-123-456-7890", "author": {"familyName": null}, "tags": ["example"]}"#,
+                r#"{"title": "Hello!", "author": {"givenName": "John"}, "tags": ["example"], "content": "This will be unchanged", "phoneNumber": "+01-123-456-7890"}"#,
+            ),
+            (r#"{"a": "b"}"#, r#"{"a": "c"}"#, r#"{"a": "c"}"#),
+            (r#"{"a": "b"}"#, r#"{"b": "c"}"#, r#"{"a": "b", "b": "c"}"#),
+            (r#"{"a": "b"}"#, r#"{"a": null}"#, r#"{}"#),
+            (r#"{"a": "b", "b": "c"}"#, r#"{"a": null}"#, r#"{"b": "c"}"#),
+            (r#"{"a": ["b"]}"#, r#"{"a": "c"}"#, r#"{"a": "c"}"#),
+            (r#"{"a": "c"}"#, r#"{"a": ["b"]}"#, r#"{"a": ["b"]}"#),
+            (r#"{"a": {"b": "c"}}"#, r#"{"a": {"b": "d", "c": null}}"#, r#"{"a": {"b": "d"}}"#),
+            (r#"{"a": [{"b": "c"}]}"#, r#"{"a": [1]}"#, r#"{"a": [1]}"#),
+            (r#"["a", "b"]"#, r#"["c", "d"]"#, r#"["c", "d"]"#),
+            (r#"{"a": "b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a": "foo"}"#, "null", "null"),
+            (r#"{"a": "foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e": null}"#, r#"{"a": 1}"#, r#"{"e": null, "a": 1}"#),
+            (r#"[1, 2]"#, r#"{"a": "b", "c": null}"#, r#"{"a": "b"}"#),
+            (r#"{}"#, r#"{"a": {"bb": {"ccc": null}}}"#, r#"{"a": {"bb": {}}}"#),
+        ];
+        for (target, patch, expected) in cases {
+            let mut value = Value::from_str(target)?;
+            value.apply_merge_patch(&Value::from_str(patch)?);
+            assert_eq!(value, Value::from_str(expected)?, "{target} + {patch}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use alloc::borrow::Cow;
+
+use crate::Value;
+use crate::prelude::*;
+
+/// Split a JSON Pointer (RFC 6901) into its unescaped reference tokens.
+///
+/// Returns [None] if the pointer is not empty and does not start with `/`.
+pub(crate) fn pointer_tokens(pointer: &str) -> Option<impl Iterator<Item = Cow<'_, str>>> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return None;
+    }
+    Some(pointer.split('/').skip(1).map(|token| {
+        if token.contains('~') {
+            // Order matters: `~01` must become `~1`, not `/`.
+            Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+        } else {
+            Cow::Borrowed(token)
+        }
+    }))
+}
+
+/// Escape a key for use as a JSON Pointer reference token (`~` as `~0`, `/` as `~1`).
+pub(crate) fn escape_pointer_token(token: &str) -> Cow<'_, str> {
+    if token.contains(['~', '/']) {
+        Cow::Owned(token.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Parse an array index reference token. Leading zeros and signs are not allowed.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+    || (token.len() > 1 && token.starts_with('0'))
+    || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// One step of a path for [Value::try_path]. See also the [path!](crate::path) macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathSegment<'a> {
+    /// Look up a key in a [Value::Object].
+    Key(&'a str),
+    /// Look up an element of a [Value::Array].
+    Index(usize),
+}
+
+impl<'a> From<&'a str> for PathSegment<'a> {
+    fn from(key: &'a str) -> Self {
+        PathSegment::Key(key)
+    }
+}
+
+impl From<usize> for PathSegment<'_> {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+/// Build a slice of [PathSegment]s for [Value::try_path]. String arguments become
+/// [PathSegment::Key]s and integers become [PathSegment::Index]es.
+/// ```
+/// # use bourne::{path, Value, PathSegment};
+/// let value = Value::object().with("a", Value::array().with_item(true));
+/// assert_eq!(path!["a", 0], &[PathSegment::Key("a"), PathSegment::Index(0)]);
+/// assert_eq!(value.try_path(path!["a", 0]), Some(&Value::from(true)));
+/// ```
+#[macro_export]
+macro_rules! path {
+    ($($segment:expr),* $(,)?) => {
+        &[$($crate::PathSegment::from($segment)),*]
+    };
+}
+
+impl Value {
+    /// Follow `segments` from self, returning [None] at the first step that doesn't
+    /// exist, rather than [Value::Null] like chained indexing does. A [PathSegment::Key]
+    /// only matches a [Value::Object], and a [PathSegment::Index] a [Value::Array].
+    pub fn try_path(&self, segments: &[PathSegment<'_>]) -> Option<&Value> {
+        segments.iter().try_fold(self, |value, segment| {
+            match (value, segment) {
+                (Value::Object(object), PathSegment::Key(key)) => object.get(*key),
+                (Value::Array(array), PathSegment::Index(index)) => array.get(*index),
+                _ => None,
+            }
+        })
+    }
+
+    /// Look up a [Value] by JSON Pointer (RFC 6901).
+    ///
+    /// The empty pointer `""` refers to the whole value, `/classes/0` refers to
+    /// the first element of the `classes` array. `~1` and `~0` are unescaped to
+    /// `/` and `~` respectively.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        pointer_tokens(pointer)?.try_fold(self, |value, token| {
+            match value {
+                Value::Object(object) => object.get(token.as_ref()),
+                Value::Array(array) => array.get(parse_index(&token)?),
+                _ => None,
+            }
+        })
+    }
+
+    /// Look up a mutable reference to a [Value] by JSON Pointer (RFC 6901).
+    ///
+    /// See [Value::pointer].
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        pointer_tokens(pointer)?.try_fold(self, |value, token| {
+            match value {
+                Value::Object(object) => object.get_mut(token.as_ref()),
+                Value::Array(array) => array.get_mut(parse_index(&token)?),
+                _ => None,
+            }
+        })
+    }
+
+    /// Remove the [Value] at a JSON Pointer from its parent object or array, and return it.
+    /// Array elements after it are shifted down.
+    ///
+    /// Returns [None] if the pointer doesn't resolve, or is the empty pointer `""`, as the
+    /// whole value has no parent. See [Value::pointer].
+    pub fn remove_path(&mut self, pointer: &str) -> Option<Value> {
+        let slash = pointer.rfind('/')?;
+        let token = pointer_tokens(&pointer[slash..])?.next()?;
+        let parent = self.pointer_mut(&pointer[..slash])?;
+        match parent {
+            Value::Array(_) => parent.remove_index(parse_index(&token)?),
+            _ => parent.remove(&token),
+        }
+    }
+
+    /// Check whether a JSON Pointer resolves to a [Value]. See [Value::pointer].
+    pub fn path_exists(&self, pointer: &str) -> bool {
+        self.pointer(pointer).is_some()
+    }
+
+    /// Get the [type name](Value::type_name) of the [Value] at a JSON Pointer, or [None]
+    /// if it doesn't resolve. See [Value::pointer].
+    pub fn type_at(&self, pointer: &str) -> Option<&'static str> {
+        self.pointer(pointer).map(Value::type_name)
+    }
+
+    /// Apply `f` to the [Value::String] at each of the given JSON Pointers.
+    ///
+    /// Pointers that don't resolve, or that resolve to a non-string, are skipped.
+    pub fn transform_strings_at<F: Fn(&str) -> String>(&mut self, pointers: &[&str], f: F) {
+        for pointer in pointers {
+            if let Some(Value::String(string)) = self.pointer_mut(pointer) {
+                *string = f(string);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{PathSegment, Value};
+
+    #[test]
+    fn pointer_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"{"a/b": [1, {"~c": true}], "": null}"#)?;
+        assert!(matches!(value.pointer(""), Some(Value::Object(_))));
+        assert!(matches!(value.pointer("/a~1b/1/~0c"), Some(Value::Boolean(true))));
+        assert!(matches!(value.pointer("/"), Some(Value::Null)));
+        assert!(value.pointer("/a~1b/01").is_none());
+        assert!(value.pointer("/a~1b/2").is_none());
+        assert!(value.pointer("a~1b").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn remove_path_test() -> Result<(), crate::error::ParseError> {
+        let mut value = Value::from_str(r#"{"a": {"b/c": {"d": 1, "e": 2}}, "list": [10, 20, 30]}"#)?;
+        assert_eq!(value.remove_path("/a/b~1c/d"), Some(Value::from(1i64)));
+        assert_eq!(value.remove_path("/list/1"), Some(Value::from(20i64)));
+        assert_eq!(value, Value::from_str(r#"{"a": {"b/c": {"e": 2}}, "list": [10, 30]}"#)?);
+        assert_eq!(value.remove_path("/a/b~1c"), Some(Value::from_str(r#"{"e": 2}"#)?));
+        assert_eq!(value.remove_path("/a/b~1c"), None);
+        assert_eq!(value.remove_path("/missing/x"), None);
+        assert_eq!(value.remove_path("/list/2"), None);
+        assert_eq!(value.remove_path("/list/01"), None);
+        assert_eq!(value.remove_path("/list/0/x"), None);
+        assert_eq!(value.remove_path("list"), None);
+        assert_eq!(value.remove_path(""), None);
+        assert_eq!(value, Value::from_str(r#"{"a": {}, "list": [10, 30]}"#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn path_exists_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "tag": null,
+                "name": "Fred",
+                "classes": ["Algebra", "History of Programming"],
+                "rgb_for_some_reason": { "r": 4, "g": "seven" }
+            }
+        "#)?;
+        assert!(value.path_exists("/classes/1"));
+        assert_eq!(value.type_at("/classes/1"), Some("string"));
+        assert_eq!(value.type_at("/classes"), Some("array"));
+        assert_eq!(value.type_at("/tag"), Some("null"));
+        assert_eq!(value.type_at(""), Some("object"));
+        assert!(!value.path_exists("/classes/2"));
+        assert!(!value.path_exists("/missing"));
+        assert_eq!(value.type_at("/missing"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn try_path_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"{"a": {"b": [10, {"c": "deep"}]}, "n": null}"#)?;
+        assert_eq!(value.try_path(path!["a", "b", 1, "c"]), Some(&Value::from("deep")));
+        assert_eq!(value.try_path(&[PathSegment::Key("a"), PathSegment::Key("b"), PathSegment::Index(0)]), Some(&Value::from(10i64)));
+        assert_eq!(value.try_path(path![]), Some(&value));
+        assert_eq!(value.try_path(path!["a", "missing", 0]), None);
+        assert_eq!(value.try_path(path!["a", 0]), None);
+        assert_eq!(value.try_path(path!["n", "x"]), None);
+        assert_eq!(value["a"]["missing"][0], Value::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn transform_strings_at_test() -> Result<(), crate::error::ParseError> {
+        let mut value = Value::from_str(r#"
+            {
+                "query": "fish & chips",
+                "path": "a b/c",
+                "untouched": "x y",
+                "number": 5
+            }
+        "#)?;
+        let encode = |s: &str| s.replace('&', "%26").replace(' ', "%20").replace('/', "%2F");
+        value.transform_strings_at(&["/query", "/path", "/number", "/missing"], encode);
+        assert!(matches!(&value["query"], Value::String(s) if s == "fish%20%26%20chips"));
+        assert!(matches!(&value["path"], Value::String(s) if s == "a%20b%2Fc"));
+        assert!(matches!(&value["untouched"], Value::String(s) if s == "x y"));
+        #[cfg(not(feature = "arbitrary_precision"))]
+        assert!(matches!(value["number"], Value::Number(crate::Number::Int(5))));
+        #[cfg(feature = "arbitrary_precision")]
+        assert_eq!(value["number"], 5i64);
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use alloc::borrow::Cow;
+
+use crate::error::QueryError;
+use crate::Value;
+use crate::prelude::*;
+
+/// A step of a JSONPath expression.
+#[derive(Debug, PartialEq)]
+enum Selector<'p> {
+    /// `.key` or `['key']`
+    Key(Cow<'p, str>),
+    /// `[index]`
+    Index(usize),
+    /// `.*` or `[*]`
+    Wildcard,
+    /// `..key`
+    Descendant(&'p str),
+}
+
+/// Read a `.key` style name at `start`, up to the next `.` or `[`.
+fn read_name(path: &str, start: usize) -> Result<(&str, usize), QueryError> {
+    let len = path[start..].find(['.', '[']).unwrap_or(path.len() - start);
+    if len == 0 {
+        return Err(QueryError { position: start });
+    }
+    Ok((&path[start..start + len], start + len))
+}
+
+/// Read a quoted key inside brackets, starting at the opening quote. Backslash escapes
+/// the next character. Returns the key and the index after the closing quote.
+fn read_quoted(path: &str, start: usize) -> Result<(Cow<'_, str>, usize), QueryError> {
+    let quote = path.as_bytes()[start] as char;
+    let mut key = String::new();
+    let mut escaped = false;
+    let mut chars = path[start + 1..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => {
+                escaped = true;
+                match chars.next() {
+                    Some((_, c)) => key.push(c),
+                    None => break,
+                }
+            }
+            c if c == quote => {
+                let end = start + 1 + offset;
+                let key = if escaped { Cow::Owned(key) } else { Cow::Borrowed(&path[start + 1..end]) };
+                return Ok((key, end + 1));
+            }
+            c => key.push(c),
+        }
+    }
+    Err(QueryError { position: path.len() })
+}
+
+/// Parse a JSONPath expression into [Selector]s.
+fn parse_query(path: &str) -> Result<Vec<Selector<'_>>, QueryError> {
+    if !path.starts_with('$') {
+        return Err(QueryError { position: 0 });
+    }
+    let bytes = path.as_bytes();
+    let mut selectors = Vec::new();
+    let mut index = 1;
+    while index < path.len() {
+        let (selector, next) = match bytes[index] {
+            b'.' if bytes.get(index + 1) == Some(&b'.') => {
+                let (name, next) = read_name(path, index + 2)?;
+                (Selector::Descendant(name), next)
+            }
+            b'.' if bytes.get(index + 1) == Some(&b'*') => (Selector::Wildcard, index + 2),
+            b'.' => {
+                let (name, next) = read_name(path, index + 1)?;
+                (Selector::Key(Cow::Borrowed(name)), next)
+            }
+            b'[' => {
+                let (selector, next) = match bytes.get(index + 1) {
+                    Some(b'*') => (Selector::Wildcard, index + 2),
+                    Some(b'\'' | b'"') => {
+                        let (key, next) = read_quoted(path, index + 1)?;
+                        (Selector::Key(key), next)
+                    }
+                    Some(b'0'..=b'9') => {
+                        let len = path[index + 1..].find(|c: char| !c.is_ascii_digit()).unwrap_or(path.len() - index - 1);
+                        let digits = &path[index + 1..index + 1 + len];
+                        let value = digits.parse().map_err(|_| QueryError { position: index + 1 })?;
+                        (Selector::Index(value), index + 1 + len)
+                    }
+                    _ => return Err(QueryError { position: index + 1 }),
+                };
+                if bytes.get(next) != Some(&b']') {
+                    return Err(QueryError { position: next });
+                }
+                (selector, next + 1)
+            }
+            _ => return Err(QueryError { position: index }),
+        };
+        selectors.push(selector);
+        index = next;
+    }
+    Ok(selectors)
+}
+
+fn collect_key<'a>(value: &'a Value, key: &str, found: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(array) => array.iter().for_each(|value| collect_key(value, key, found)),
+        Value::Object(object) => {
+            if let Some(value) = object.get(key) {
+                found.push(value);
+            }
+            object.values().for_each(|value| collect_key(value, key, found));
+        }
+        _ => (),
+    }
+}
+
+impl Value {
+    /// Collect every value stored under `key` in any object in the tree, like the
+    /// JSONPath `$..key`.
+    ///
+    /// Values are returned in depth-first order: an object's own match comes before
+    /// matches nested inside any of its values.
+    pub fn find_all<'a>(&'a self, key: &str) -> Vec<&'a Value> {
+        let mut found = Vec::new();
+        collect_key(self, key, &mut found);
+        found
+    }
+
+    /// Evaluate a JSONPath expression, returning every matching value. An expression
+    /// without matches gives an empty [Vec].
+    ///
+    /// Supported syntax: the root `$`, followed by any number of `.key`, `['key']` (or
+    /// `["key"]`), `[index]`, `.*` and `[*]` (every element or value), and `..key`
+    /// (every value under `key` at any depth, see [Value::find_all]).
+    /// ```
+    /// # use bourne::Value;
+    /// # use std::str::FromStr;
+    /// let value = Value::from_str(r#"{"users": [{"name": "Ann"}, {"name": "Bo"}]}"#).unwrap();
+    /// let names = value.query("$.users[*].name").unwrap();
+    /// assert_eq!(names, [&Value::from("Ann"), &Value::from("Bo")]);
+    /// ```
+    pub fn query(&self, path: &str) -> Result<Vec<&Value>, QueryError> {
+        let selectors = parse_query(path)?;
+        let mut current = vec![self];
+        for selector in selectors {
+            current = current.into_iter().flat_map(|value| -> Vec<&Value> {
+                match (&selector, value) {
+                    (Selector::Key(key), Value::Object(object)) => object.get(key.as_ref()).into_iter().collect(),
+                    (Selector::Index(index), Value::Array(array)) => array.get(*index).into_iter().collect(),
+                    (Selector::Wildcard, Value::Array(array)) => array.iter().collect(),
+                    (Selector::Wildcard, Value::Object(object)) => object.values().collect(),
+                    (Selector::Descendant(key), value) => value.find_all(key),
+                    _ => Vec::new(),
+                }
+            }).collect();
+        }
+        Ok(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::error::QueryError;
+    use crate::Value;
+
+    #[test]
+    fn find_all_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "id": 1,
+                "children": [
+                    { "id": 2, "children": [{ "id": 3 }] },
+                    { "name": "no id" },
+                    { "id": { "id": 4 } }
+                ]
+            }
+        "#)?;
+        let ids = value.find_all("id");
+        assert_eq!(ids.len(), 5);
+        assert_eq!(ids[..3], [&Value::from(1i64), &Value::from(2i64), &Value::from(3i64)]);
+        assert_eq!(ids[3]["id"], 4i64);
+        assert_eq!(ids[4], &Value::from(4i64));
+        assert_eq!(value.find_all("name"), [&Value::from("no id")]);
+        assert!(value.find_all("missing").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn query_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "tag": null,
+                "name": "Fred",
+                "classes": ["Algebra", "History of Programming", "Cryptography"],
+                "rgb_for_some_reason": { "r": 4, "g": 7, "b": 3 },
+                "nested": [{ "g": 8 }]
+            }
+        "#)?;
+        let classes = value.query("$.classes[*]").unwrap();
+        assert_eq!(classes, [&Value::from("Algebra"), &Value::from("History of Programming"), &Value::from("Cryptography")]);
+        let mut greens = value.query("$..g").unwrap();
+        greens.sort_by_key(|green| green.as_i64());
+        assert_eq!(greens, [&Value::from(7i64), &Value::from(8i64)]);
+        assert_eq!(value.query("$").unwrap(), [&value]);
+        assert_eq!(value.query("$.classes[1]").unwrap(), [&Value::from("History of Programming")]);
+        assert_eq!(value.query("$['rgb_for_some_reason'][\"r\"]").unwrap(), [&Value::from(4i64)]);
+        assert_eq!(value.query("$.rgb_for_some_reason.*").unwrap().len(), 3);
+        assert_eq!(value.query("$.nested[0].g").unwrap(), [&Value::from(8i64)]);
+        assert!(value.query("$.classes[9]").unwrap().is_empty());
+        assert!(value.query("$.name.first").unwrap().is_empty());
+        assert!(value.query("$.missing[*]").unwrap().is_empty());
+        for (path, position) in [("classes", 0), ("$.", 2), ("$..", 3), ("$[", 2), ("$[1", 3), ("$['a'", 5), ("$['a", 4), ("$x", 1), ("$[-1]", 2)] {
+            assert_eq!(value.query(path), Err(QueryError { position }), "{path}");
+        }
+        Ok(())
+    }
+}
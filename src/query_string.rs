@@ -0,0 +1,85 @@
+use core::fmt::Write;
+
+use crate::error::QueryStringError;
+use crate::Value;
+use crate::prelude::*;
+
+/// Percent-encode `text` for a query string component. Everything except the unreserved
+/// characters of RFC 3986 (`A-Z a-z 0-9 - . _ ~`) is encoded as UTF-8 bytes, so a space
+/// becomes `%20`.
+fn percent_encode(text: &str, buffer: &mut String) {
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            buffer.push(byte as char);
+        } else {
+            write!(buffer, "%{byte:02X}").unwrap();
+        }
+    }
+}
+
+impl Value {
+    /// Encode a flat [Value::Object] as a URL query string, like `a=1&b=x%20y`.
+    ///
+    /// Keys and values are percent-encoded (see [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-2.3)),
+    /// with spaces as `%20` rather than `+`. Strings are used verbatim, [Value::Null] becomes
+    /// an empty value (`a=`), and other scalars use their JSON text. The pairs are sorted
+    /// by key.
+    ///
+    /// Returns [QueryStringError::NotAnObject] if self is not a [Value::Object], and
+    /// [QueryStringError::Nested] if a value is an array or an object.
+    pub fn to_query_string(&self) -> Result<String, QueryStringError> {
+        let Value::Object(object) = self else {
+            return Err(QueryStringError::NotAnObject(self.type_name()));
+        };
+        let mut entries = object.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        let mut buffer = String::new();
+        for (key, value) in entries {
+            if !buffer.is_empty() {
+                buffer.push('&');
+            }
+            percent_encode(key, &mut buffer);
+            buffer.push('=');
+            match value {
+                Value::Null => (),
+                Value::String(string) => percent_encode(string, &mut buffer),
+                Value::Array(_) | Value::Object(_) => return Err(QueryStringError::Nested(key.to_string())),
+                scalar => percent_encode(&scalar.to_string(), &mut buffer),
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::error::QueryStringError;
+    use crate::Value;
+
+    #[test]
+    fn to_query_string_test() -> Result<(), crate::error::ParseError> {
+        let query = Value::from_str(r#"
+            {
+                "q": "fish & chips",
+                "page": 2,
+                "exact": true,
+                "filter": null,
+                "sort by": "price/asc",
+                "café": "crème brûlée~"
+            }
+        "#)?;
+        assert_eq!(
+            query.to_query_string(),
+            Ok("caf%C3%A9=cr%C3%A8me%20br%C3%BBl%C3%A9e~&exact=true&filter=&page=2&q=fish%20%26%20chips&sort%20by=price%2Fasc".to_string()),
+        );
+        assert_eq!(Value::object().to_query_string(), Ok(String::new()));
+        assert_eq!(
+            Value::from_str(r#"{"a": 1, "tags": ["x", "y"]}"#)?.to_query_string(),
+            Err(QueryStringError::Nested("tags".to_string())),
+        );
+        assert_eq!(Value::from("a=1").to_query_string(), Err(QueryStringError::NotAnObject("string")));
+        Ok(())
+    }
+}
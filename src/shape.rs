@@ -0,0 +1,75 @@
+use alloc::collections::BTreeMap;
+
+use crate::Value;
+use crate::prelude::*;
+
+/// A shallow summary of a [Value], returned by [Value::shape].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    /// A [Value::Object] with `len` keys, in iteration order.
+    Object { len: usize, keys: Vec<String> },
+    /// A [Value::Array] with `len` elements, and how many elements there are of each
+    /// [type name](Value::type_name).
+    Array { len: usize, types: BTreeMap<&'static str, usize> },
+    /// Any other [Value], by [type name](Value::type_name).
+    Scalar(&'static str),
+}
+
+impl Value {
+    /// Summarize the top level of self without descending into it, for a quick look at
+    /// an unknown document. See [Shape].
+    pub fn shape(&self) -> Shape {
+        match self {
+            Value::Object(object) => Shape::Object {
+                len: object.len(),
+                keys: object.keys().map(|key| key.to_string()).collect(),
+            },
+            Value::Array(array) => Shape::Array {
+                len: array.len(),
+                types: array.iter().fold(BTreeMap::new(), |mut types, value| {
+                    *types.entry(value.type_name()).or_insert(0) += 1;
+                    types
+                }),
+            },
+            scalar => Shape::Scalar(scalar.type_name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Shape, Value};
+
+    #[test]
+    fn shape_test() -> Result<(), crate::error::ParseError> {
+        let value = Value::from_str(r#"
+            {
+                "tag": null,
+                "registered": true,
+                "age": 197,
+                "name": "Fred",
+                "classes": [
+                    "Algebra",
+                    "History of Programming",
+                    "Algorithms and Datastructures",
+                    "Cryptography"
+                ],
+                "rgb_for_some_reason": { "r": 4, "g": 7, "b": 3 }
+            }
+        "#)?;
+        let Shape::Object { len, mut keys } = value.shape() else { panic!() };
+        keys.sort();
+        assert_eq!(len, 6);
+        assert_eq!(keys, ["age", "classes", "name", "registered", "rgb_for_some_reason", "tag"]);
+        assert_eq!(value["classes"].shape(), Shape::Array { len: 4, types: [("string", 4)].into() });
+        assert_eq!(value["age"].shape(), Shape::Scalar("number"));
+        assert_eq!(value["tag"].shape(), Shape::Scalar("null"));
+        assert_eq!(
+            Value::from_str(r#"[1, "a", 2, null, [3]]"#)?.shape(),
+            Shape::Array { len: 5, types: [("array", 1), ("null", 1), ("number", 2), ("string", 1)].into() },
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+use std::io::{ErrorKind, Read};
+use std::str::FromStr;
+
+use crate::error::ParseError;
+use crate::parse::ParseResult;
+use crate::Value;
+
+/// How many bytes [ArrayElements] reads from its reader at a time.
+const CHUNK_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the opening `[`.
+    Start,
+    /// Before an element.
+    Element,
+    /// After the closing `]`, where only whitespace may follow.
+    End,
+    /// Finished, or failed.
+    Done,
+}
+
+/// Where an element ends, found by scanning for a `,`, `]` or `}` outside of strings
+/// and nested arrays and objects. Kept between reads so each byte is scanned once.
+#[derive(Debug, Default, Clone, Copy)]
+struct Scan {
+    position: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl Scan {
+    /// Scan `bytes` from where the last call left off, returning the index of the byte
+    /// that ends the element, if it has been read yet.
+    fn find_end(&mut self, bytes: &[u8]) -> Option<usize> {
+        while let Some(&byte) = bytes.get(self.position) {
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => (),
+                }
+            } else {
+                match byte {
+                    b'"' => self.in_string = true,
+                    b'[' | b'{' => self.depth += 1,
+                    b',' | b']' | b'}' if self.depth == 0 => return Some(self.position),
+                    b']' | b'}' => self.depth -= 1,
+                    _ => (),
+                }
+            }
+            self.position += 1;
+        }
+        None
+    }
+}
+
+/// Lazily parses the elements of a JSON array read from `R`.
+struct ArrayElements<R> {
+    reader: R,
+    /// Bytes read but not yet consumed.
+    buffer: Vec<u8>,
+    /// Index in the whole input of `buffer[0]`.
+    offset: usize,
+    eof: bool,
+    state: State,
+    scan: Scan,
+    /// The number of elements yielded so far.
+    count: usize,
+}
+
+impl<R: Read> ArrayElements<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            offset: 0,
+            eof: false,
+            state: State::Start,
+            scan: Scan::default(),
+            count: 0,
+        }
+    }
+
+    /// Read another chunk into the buffer. Returns false at the end of the input.
+    fn fill(&mut self) -> ParseResult<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        let start = self.buffer.len();
+        self.buffer.resize(start + CHUNK_SIZE, 0);
+        let read = loop {
+            match self.reader.read(&mut self.buffer[start..]) {
+                Ok(read) => break read,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.buffer.truncate(start);
+                    return Err(error.into());
+                }
+            }
+        };
+        self.buffer.truncate(start + read);
+        self.eof = read == 0;
+        Ok(!self.eof)
+    }
+
+    /// Drop the first `count` bytes of the buffer.
+    fn consume(&mut self, count: usize) {
+        self.buffer.drain(..count);
+        self.offset += count;
+    }
+
+    /// Skip whitespace, reading as needed, and return the next byte without consuming it.
+    fn peek_token(&mut self) -> ParseResult<Option<u8>> {
+        loop {
+            let whitespace = self.buffer.iter().take_while(|byte| byte.is_ascii_whitespace()).count();
+            self.consume(whitespace);
+            if let Some(&byte) = self.buffer.first() {
+                return Ok(Some(byte));
+            }
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// The error for an unexpected byte at the start of the buffer.
+    fn expected(&self, expected: &'static str) -> ParseError {
+        let found = String::from_utf8_lossy(&self.buffer[..self.buffer.len().min(4)]).chars().next().unwrap_or('\u{fffd}');
+        ParseError::Expected { expected, found, position: self.offset }
+    }
+
+    fn next_element(&mut self) -> ParseResult<Option<Value>> {
+        loop {
+            match self.state {
+                State::Start => {
+                    match self.peek_token()? {
+                        Some(b'[') => self.consume(1),
+                        Some(_) => return Err(self.expected("'['")),
+                        None => return Err(ParseError::UnexpectedEOF),
+                    }
+                    match self.peek_token()? {
+                        Some(b']') => {
+                            self.consume(1);
+                            self.state = State::End;
+                        }
+                        Some(_) => self.state = State::Element,
+                        None => return Err(ParseError::UnexpectedEOF),
+                    }
+                }
+                State::Element => {
+                    let end = loop {
+                        if let Some(end) = self.scan.find_end(&self.buffer) {
+                            break end;
+                        }
+                        if !self.fill()? {
+                            return Err(ParseError::UnexpectedEOF);
+                        }
+                    };
+                    self.scan = Scan::default();
+                    self.count += 1;
+                    let record = self.count;
+                    let value = std::str::from_utf8(&self.buffer[..end])
+                        .map_err(ParseError::from)
+                        .and_then(Value::from_str)
+                        .map_err(|error| ParseError::Record { record, error: Box::new(error) })?;
+                    self.consume(end);
+                    match self.buffer[0] {
+                        b',' => self.consume(1),
+                        b']' => {
+                            self.consume(1);
+                            self.state = State::End;
+                        }
+                        _ => return Err(self.expected("',' or ']'")),
+                    }
+                    return Ok(Some(value));
+                }
+                State::End => {
+                    self.state = State::Done;
+                    return match self.peek_token()? {
+                        Some(_) => Err(ParseError::InvalidCharacter(self.offset)),
+                        None => Ok(None),
+                    };
+                }
+                State::Done => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for ArrayElements<R> {
+    type Item = ParseResult<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.next_element();
+        if element.is_err() {
+            self.state = State::Done;
+        }
+        element.transpose()
+    }
+}
+
+impl Value {
+    /// Parse a JSON [Value] from a reader. The whole input is read before parsing.
+    pub fn from_reader<R: Read>(mut reader: R) -> ParseResult<Value> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Value::from_slice(&bytes)
+    }
+
+    /// Lazily parse the elements of a top-level JSON array read from `reader`, yielding
+    /// one element at a time. Only the element being parsed is held in memory, so this
+    /// can process arrays far larger than would fit as a [Value].
+    ///
+    /// Errors in an element are wrapped in [ParseError::Record] with the element's
+    /// number, starting at 1; indices in those are relative to the start of the element.
+    /// Other indices are relative to the whole input. Iteration stops after the first error.
+    pub fn stream_array_elements<R: Read>(reader: R) -> impl Iterator<Item = ParseResult<Value>> {
+        ArrayElements::new(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    /// A reader that returns at most `step` bytes per read, to exercise refilling.
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let count = self.step.min(buf.len()).min(self.bytes.len());
+            buf[..count].copy_from_slice(&self.bytes[..count]);
+            self.bytes = &self.bytes[count..];
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn stream_array_elements_test() -> ParseResult<()> {
+        let source = r#" [1, "two, ]", {"three": [3, {"}": "]"}]}, [4.5], null ] "#;
+        let expected = Value::from_str(source)?.into_array().unwrap();
+        for step in [1, 3, 1000] {
+            let reader = Trickle { bytes: source.as_bytes(), step };
+            let elements = Value::stream_array_elements(reader).collect::<ParseResult<Vec<_>>>()?;
+            assert_eq!(elements, expected);
+        }
+        assert_eq!(Value::stream_array_elements(" [ ] ".as_bytes()).count(), 0);
+        assert_eq!(Value::from_reader(source.as_bytes())?, Value::from_str(source)?);
+        Ok(())
+    }
+
+    #[test]
+    fn stream_array_elements_error_test() {
+        let mut elements = Value::stream_array_elements(r#"[1, 2, {"a": tru}, 4, 5]"#.as_bytes());
+        assert!(matches!(elements.next(), Some(Ok(value)) if value == 1i64));
+        assert!(matches!(elements.next(), Some(Ok(value)) if value == 2i64));
+        assert!(matches!(
+            elements.next(),
+            Some(Err(ParseError::Record { record: 3, error })) if matches!(*error, ParseError::InvalidCharacter(_)),
+        ));
+        assert!(elements.next().is_none());
+
+        let errors = |source: &'static str| {
+            Value::stream_array_elements(source.as_bytes()).find_map(Result::err).unwrap()
+        };
+        assert!(matches!(errors(r#" {"a": 1}"#), ParseError::Expected { position: 1, found: '{', .. }));
+        assert!(matches!(errors("[1, 2"), ParseError::UnexpectedEOF));
+        assert!(matches!(errors("[1, 2}"), ParseError::Expected { position: 5, found: '}', .. }));
+        assert!(matches!(errors("[1,, 2]"), ParseError::Record { record: 2, .. }));
+        assert!(matches!(errors("[1] 2"), ParseError::InvalidCharacter(4)));
+        assert!(matches!(errors(""), ParseError::UnexpectedEOF));
+
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk on fire"))
+            }
+        }
+        let error = Value::stream_array_elements(Failing).next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), crate::error::ErrorKind::Io);
+        assert!(matches!(Value::from_reader(Failing), Err(ParseError::Io(_))));
+    }
+}
@@ -0,0 +1,69 @@
+//! Allocation counts for parsing. This lives in its own test binary because it installs a
+//! counting `#[global_allocator]`, which would otherwise apply to every unit test.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::str::FromStr;
+
+use bourne::Value;
+
+/// Counts allocations made by the current thread, so tests can bound them.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations `f` makes on this thread.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn repeated_keys_allocation_test() -> Result<(), bourne::error::ParseError> {
+    let records = 1000;
+    let record = r#"{"id": 1, "name": "x", "active": true}"#;
+    let source = format!("[{}]", vec![record; records].join(","));
+    let (value, count) = count_allocations(|| Value::from_str(&source));
+    let value = value?;
+    assert_eq!(value.len(), records);
+    assert!(value.as_array().unwrap().iter().all(|record| record["name"] == "x" && record.len() == 3));
+    // Per record: the map (two allocations with `preserve_order`), the string value, the
+    // number text with `arbitrary_precision`, and the three keys, unless `shared_keys`
+    // lets them share the first record's. The outer array adds a few reallocations.
+    let per_record = 2
+        + usize::from(cfg!(feature = "preserve_order"))
+        + usize::from(cfg!(feature = "arbitrary_precision"))
+        + if cfg!(feature = "shared_keys") { 0 } else { 3 };
+    assert!(count <= records * per_record + 20, "{count} allocations");
+    Ok(())
+}
+
+#[cfg(feature = "shared_keys")]
+#[test]
+fn shared_keys_test() -> Result<(), bourne::error::ParseError> {
+    let value = Value::from_str(r#"[{"id": 1, "tags": {"id": 2}}, {"id": 3}]"#)?;
+    let key = |value: &Value| value.as_object().unwrap().get_key_value("id").unwrap().0.clone();
+    let first = key(&value[0]);
+    assert!(std::sync::Arc::ptr_eq(&first, &key(&value[1])));
+    assert!(std::sync::Arc::ptr_eq(&first, &key(&value[0]["tags"])));
+    // Separate documents don't share keys.
+    assert!(!std::sync::Arc::ptr_eq(&first, &key(&Value::from_str(r#"{"id": 1}"#)?)));
+    Ok(())
+}
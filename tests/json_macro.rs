@@ -0,0 +1,22 @@
+//! The `json!` macro expands in the user's crate, so it's tested from outside `bourne`,
+//! under every feature set.
+
+use std::str::FromStr;
+
+use bourne::{json, Value};
+
+#[test]
+fn json_macro_test() -> Result<(), bourne::error::ParseError> {
+    let name = "bourne";
+    let key = String::from("owned key");
+    let value = json!({
+        "name": name,
+        "list": [1i64, null, true],
+        "nested": { "a": 2.5 },
+        key: {}
+    });
+    let expected = r#"{"name": "bourne", "list": [1, null, true], "nested": {"a": 2.5}, "owned key": {}}"#;
+    assert_eq!(value, Value::from_str(expected)?);
+    assert_eq!(json!(), Value::Null);
+    Ok(())
+}